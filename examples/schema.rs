@@ -3,7 +3,17 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
-use bridge::msg::{HandleMsg, InitMsg, QueryMsg};
+use bridge::events::{
+    ActionCompleted, BridgeEventInfo, EncryptedEvent, ExecutedEventInfo, GuardianPause, Refund, Rescue, RescueQueued,
+    TransferSnip721, TransferSnip721Batch, UnfreezeSnip721, UnfreezeSnip721Batch,
+};
+use bridge::msg::{
+    ActionConsumedResponse, ActionsConsumedResponse, ChainNonceResponse, ConsumedActionsResponse, ChainResponse, ChainsResponse, ConfigResponse,
+    EventCntResponse, ForeignTokenResponse, GroupKeyResponse, GuardiansResponse, HandleMsg, HistoryResponse,
+    InCustodyResponse, InitMsg, OutboundResponse, PausedResponse, QueryMsg, RateLimitResponse, ReceiptResponse,
+    SigningPayloadResponse, VerifySignatureResponse, ViewingKeyResponse, WhitelistResponse,
+    WhitelistedResponse, WrappedTokenResponse,
+};
 use bridge::state::State;
 
 fn main() {
@@ -16,4 +26,41 @@ fn main() {
     export_schema(&schema_for!(HandleMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(State), &out_dir);
+
+    export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(PausedResponse), &out_dir);
+    export_schema(&schema_for!(GroupKeyResponse), &out_dir);
+    export_schema(&schema_for!(ChainNonceResponse), &out_dir);
+    export_schema(&schema_for!(EventCntResponse), &out_dir);
+    export_schema(&schema_for!(GuardiansResponse), &out_dir);
+    export_schema(&schema_for!(WhitelistedResponse), &out_dir);
+    export_schema(&schema_for!(WhitelistResponse), &out_dir);
+    export_schema(&schema_for!(ChainResponse), &out_dir);
+    export_schema(&schema_for!(ChainsResponse), &out_dir);
+    export_schema(&schema_for!(RateLimitResponse), &out_dir);
+    export_schema(&schema_for!(ReceiptResponse), &out_dir);
+    export_schema(&schema_for!(InCustodyResponse), &out_dir);
+    export_schema(&schema_for!(OutboundResponse), &out_dir);
+    export_schema(&schema_for!(WrappedTokenResponse), &out_dir);
+    export_schema(&schema_for!(ForeignTokenResponse), &out_dir);
+    export_schema(&schema_for!(ActionConsumedResponse), &out_dir);
+    export_schema(&schema_for!(ActionsConsumedResponse), &out_dir);
+    export_schema(&schema_for!(ConsumedActionsResponse), &out_dir);
+    export_schema(&schema_for!(HistoryResponse), &out_dir);
+    export_schema(&schema_for!(ViewingKeyResponse), &out_dir);
+    export_schema(&schema_for!(SigningPayloadResponse), &out_dir);
+    export_schema(&schema_for!(VerifySignatureResponse), &out_dir);
+
+    export_schema(&schema_for!(BridgeEventInfo), &out_dir);
+    export_schema(&schema_for!(ExecutedEventInfo), &out_dir);
+    export_schema(&schema_for!(ActionCompleted), &out_dir);
+    export_schema(&schema_for!(TransferSnip721), &out_dir);
+    export_schema(&schema_for!(TransferSnip721Batch), &out_dir);
+    export_schema(&schema_for!(UnfreezeSnip721), &out_dir);
+    export_schema(&schema_for!(UnfreezeSnip721Batch), &out_dir);
+    export_schema(&schema_for!(GuardianPause), &out_dir);
+    export_schema(&schema_for!(Rescue), &out_dir);
+    export_schema(&schema_for!(RescueQueued), &out_dir);
+    export_schema(&schema_for!(Refund), &out_dir);
+    export_schema(&schema_for!(EncryptedEvent), &out_dir);
 }
@@ -0,0 +1,190 @@
+use std::env::current_dir;
+use std::fs::{create_dir_all, write};
+
+use cosmwasm_std::{Binary, HumanAddr};
+use ed25519_dalek::{ExpandedSecretKey, PublicKey, SecretKey};
+use serde::Serialize;
+
+use bridge::msg::*;
+use bridge::signing::signing_digest;
+
+/// Seed of the key signing every vector, never use it outside of tests
+const TEST_SEED: [u8; 32] = [
+    0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+    0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f,
+];
+const CHAIN_NONCE: u64 = 1;
+const SC_ADDR: &str = "secret1bridge";
+
+#[derive(Serialize)]
+struct SigningVectors {
+    public_key: Binary,
+    vectors: Vec<SigningVector>,
+}
+
+#[derive(Serialize)]
+struct SigningVector {
+    name: String,
+    chain_nonce: u64,
+    sc_addr: HumanAddr,
+    action_id: u128,
+    msg: SignableMsg,
+    context: String,
+    /// Borsh encoded `BridgeAction`
+    action: Binary,
+    /// SHA-512 of `context || action`
+    digest: Binary,
+    signature: Binary,
+}
+
+fn mint_args(token_id: &str) -> MintArgs {
+    MintArgs {
+        minter: "wrapped".into(),
+        minter_hash: "hash".into(),
+        token_uri: format!("ipfs://{}", token_id),
+        contract: "collection".into(),
+        token_id: token_id.into(),
+    }
+}
+
+fn unfreeze_args(token_id: &str) -> UnfreezeArgs {
+    UnfreezeArgs {
+        contract: "collection".into(),
+        contract_hash: "hash".into(),
+        token_id: token_id.into(),
+    }
+}
+
+fn messages() -> Vec<(&'static str, SignableMsg)> {
+    let collection: Vec<u8> = (1..=20).collect();
+
+    vec![
+        ("set_pause", SignableMsg::SetPause(SetPause(true))),
+        ("withdraw_fees", SignableMsg::WithdrawFees(WithdrawFees("treasury".into()))),
+        ("set_group_key", SignableMsg::SetGroupKey(SetGroupKey([0x11; 32]))),
+        ("whitelist_nft", SignableMsg::WhitelistNft(WhitelistNft(collection.clone()))),
+        ("unwhitelist_nft", SignableMsg::UnwhitelistNft(UnwhitelistNft(collection.clone()))),
+        ("whitelist_nft_batch", SignableMsg::WhitelistNftBatch(WhitelistNftBatch {
+            add: vec![collection],
+            remove: vec![vec![0xff; 20]],
+        })),
+        ("set_guardians", SignableMsg::SetGuardians(SetGuardians(vec!["guardian".into()]))),
+        ("register_chain", SignableMsg::RegisterChain(RegisterChain {
+            chain_nonce: 2,
+            name: "Ethereum".into(),
+            address_format: AddressFormat::EvmHex,
+        })),
+        ("register_chain_bech32", SignableMsg::RegisterChain(RegisterChain {
+            chain_nonce: 3,
+            name: "Cosmos".into(),
+            address_format: AddressFormat::Bech32 { hrp: "cosmos".into() },
+        })),
+        ("unregister_chain", SignableMsg::UnregisterChain(UnregisterChain(2))),
+        ("set_rate_limit", SignableMsg::SetRateLimit(SetRateLimit {
+            direction: Direction::Outbound,
+            collection: Some("collection".into()),
+            limit: Some(RateLimit { max_tokens: 10, window_blocks: 100 }),
+        })),
+        ("clear_rate_limit", SignableMsg::SetRateLimit(SetRateLimit {
+            direction: Direction::Inbound,
+            collection: None,
+            limit: None,
+        })),
+        ("rescue_nft", SignableMsg::RescueNft(RescueNft {
+            contract: "collection".into(),
+            contract_hash: "hash".into(),
+            token_id: "1".into(),
+            to: "treasury".into(),
+            force: false,
+        })),
+        ("rescue_snip20", SignableMsg::RescueSnip20(RescueSnip20 {
+            contract: "token".into(),
+            contract_hash: "hash".into(),
+            amount: 1_000_000,
+            to: "treasury".into(),
+        })),
+        ("rescue_native", SignableMsg::RescueNative(RescueNative {
+            denom: "uscrt".into(),
+            amount: u128::MAX,
+            to: "treasury".into(),
+        })),
+        ("set_event_key", SignableMsg::SetEventKey(SetEventKey(Some([0x22; 32])))),
+        ("clear_event_key", SignableMsg::SetEventKey(SetEventKey(None))),
+        ("validate_transfer_nft", SignableMsg::ValidateTransferNft(ValidateTransferNft {
+            mint_args: mint_args("1"),
+            to: "receiver".into(),
+            source_chain_nonce: 2,
+        })),
+        ("validate_transfer_nft_batch", SignableMsg::ValidateTransferNftBatch(ValidateTransferNftBatch {
+            mint_args: vec![mint_args("1"), mint_args("2")],
+            to: "receiver".into(),
+            source_chain_nonce: 2,
+        })),
+        ("validate_unfreeze_nft", SignableMsg::ValidateUnfreezeNft(ValidateUnfreezeNft {
+            unfreeze_args: unfreeze_args("1"),
+            to: "receiver".into(),
+            source_chain_nonce: 2,
+        })),
+        ("validate_unfreeze_nft_batch", SignableMsg::ValidateUnfreezeNftBatch(ValidateUnfreezeNftBatch {
+            unfreeze_args: vec![unfreeze_args("1"), unfreeze_args("2")],
+            to: "receiver".into(),
+            source_chain_nonce: 2,
+        })),
+        ("refund_freeze", SignableMsg::RefundFreeze(RefundFreeze { action_id: 7, refund_fee: true })),
+        ("refund_withdraw", SignableMsg::RefundWithdraw(RefundWithdraw { action_id: 8, refund_fee: false })),
+        ("claim_transfer", SignableMsg::ClaimTransfer(ClaimTransfer(ValidateTransferNft {
+            mint_args: mint_args("1"),
+            to: "receiver".into(),
+            source_chain_nonce: 2,
+        }))),
+        ("claim_transfer_batch", SignableMsg::ClaimTransferBatch(ClaimTransferBatch(ValidateTransferNftBatch {
+            mint_args: vec![mint_args("1"), mint_args("2")],
+            to: "receiver".into(),
+            source_chain_nonce: 2,
+        }))),
+    ]
+}
+
+/// Writes `vectors/signed_payloads.json`, checked against the contract by the unit tests
+fn main() {
+    let secret = SecretKey::from_bytes(&TEST_SEED).unwrap();
+    let public: PublicKey = (&secret).into();
+    let expanded: ExpandedSecretKey = (&secret).into();
+    let sc_addr = HumanAddr(SC_ADDR.into());
+
+    let vectors = messages()
+        .into_iter()
+        .enumerate()
+        .map(|(i, (name, msg))| {
+            // every byte of the id is set so its encoding is unambiguous
+            let action_id = u128::MAX - i as u128;
+            let action = msg.action_bytes(&sc_addr, action_id, CHAIN_NONCE).unwrap();
+            let digest = signing_digest(msg.context(), &action);
+            let signature = expanded.sign(&digest, &public).to_bytes().to_vec();
+
+            SigningVector {
+                name: name.into(),
+                chain_nonce: CHAIN_NONCE,
+                sc_addr: sc_addr.clone(),
+                action_id,
+                context: String::from_utf8(msg.context().to_vec()).unwrap(),
+                msg,
+                action: Binary(action),
+                digest: Binary(digest),
+                signature: Binary(signature),
+            }
+        })
+        .collect();
+
+    let out = SigningVectors {
+        public_key: Binary(public.to_bytes().to_vec()),
+        vectors,
+    };
+
+    let mut path = current_dir().unwrap();
+    path.push("vectors");
+    create_dir_all(&path).unwrap();
+    path.push("signed_payloads.json");
+    write(&path, serde_json::to_string_pretty(&out).unwrap() + "\n").unwrap();
+    println!("wrote {}", path.display());
+}
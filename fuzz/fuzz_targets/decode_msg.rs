@@ -0,0 +1,20 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use cosmwasm_std::{from_slice, to_vec};
+
+use bridge::msg::{HandleMsg, QueryMsg};
+
+// Whatever bytes a client submits, decoding either fails or yields a message
+// that encodes back to itself
+fuzz_target!(|data: &[u8]| {
+    if let Ok(msg) = from_slice::<HandleMsg>(data) {
+        let bin = to_vec(&msg).unwrap();
+        assert_eq!(from_slice::<HandleMsg>(&bin).unwrap(), msg);
+    }
+
+    if let Ok(msg) = from_slice::<QueryMsg>(data) {
+        let bin = to_vec(&msg).unwrap();
+        assert_eq!(from_slice::<QueryMsg>(&bin).unwrap(), msg);
+    }
+});
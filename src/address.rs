@@ -0,0 +1,123 @@
+use sha2::{Digest, Sha256};
+
+use crate::msg::AddressFormat;
+
+const BECH32_CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const BECH32_GEN: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+const TEZOS_PREFIXES: [(&str, [u8; 3]); 4] = [
+    ("tz1", [6, 161, 159]),
+    ("tz2", [6, 161, 161]),
+    ("tz3", [6, 161, 164]),
+    ("KT1", [2, 90, 121]),
+];
+
+/// Check that `addr` is a well formed address for the given destination chain format
+pub fn validate_address(format: &AddressFormat, addr: &str) -> bool {
+    match format {
+        AddressFormat::EvmHex => validate_evm(addr),
+        AddressFormat::Bech32 { hrp } => bech32_decode(addr)
+            .map(|(h, _)| &h == hrp)
+            .unwrap_or(false),
+        // erd1 bech32 encoding of a 32 byte public key
+        AddressFormat::Elrond => bech32_decode(addr)
+            .map(|(h, data)| h == "erd" && data.len() == 52)
+            .unwrap_or(false),
+        AddressFormat::Tezos => validate_tezos(addr),
+        AddressFormat::Solana => base58_decode(addr)
+            .map(|d| d.len() == 32)
+            .unwrap_or(false),
+    }
+}
+
+fn validate_evm(addr: &str) -> bool {
+    addr.len() == 42
+        && addr.starts_with("0x")
+        && addr[2..].bytes().all(|c| c.is_ascii_hexdigit())
+}
+
+fn validate_tezos(addr: &str) -> bool {
+    let prefix = match TEZOS_PREFIXES.iter().find(|(p, _)| addr.starts_with(p)) {
+        Some((_, prefix)) => prefix,
+        None => return false,
+    };
+    let raw = match base58_decode(addr) {
+        Some(raw) if raw.len() == 27 => raw,
+        _ => return false,
+    };
+    let (payload, checksum) = raw.split_at(23);
+
+    let hash = Sha256::digest(&Sha256::digest(payload));
+    &payload[..3] == prefix && &hash[..4] == checksum
+}
+
+fn bech32_polymod(values: &[u8]) -> u32 {
+    let mut chk: u32 = 1;
+    for v in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x1ffffff) << 5) ^ (*v as u32);
+        for (i, gen) in BECH32_GEN.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= gen;
+            }
+        }
+    }
+
+    chk
+}
+
+/// Decode a bech32 string, returning the hrp and the 5 bit data words without the checksum
+fn bech32_decode(addr: &str) -> Option<(String, Vec<u8>)> {
+    if !addr.is_ascii() || addr.len() > 90 {
+        return None;
+    }
+    if addr.to_ascii_lowercase() != addr && addr.to_ascii_uppercase() != addr {
+        return None;
+    }
+
+    let addr = addr.to_ascii_lowercase();
+    let sep = addr.rfind('1')?;
+    if sep == 0 || sep + 7 > addr.len() {
+        return None;
+    }
+
+    let (hrp, data) = (&addr[..sep], &addr[sep + 1..]);
+    let data = data
+        .bytes()
+        .map(|c| BECH32_CHARSET.iter().position(|&x| x == c).map(|p| p as u8))
+        .collect::<Option<Vec<_>>>()?;
+
+    let mut values: Vec<u8> = hrp.bytes().map(|c| c >> 5).collect();
+    values.push(0);
+    values.extend(hrp.bytes().map(|c| c & 31));
+    values.extend(&data);
+    if bech32_polymod(&values) != 1 {
+        return None;
+    }
+
+    Some((hrp.to_string(), data[..data.len() - 6].to_vec()))
+}
+
+fn base58_decode(addr: &str) -> Option<Vec<u8>> {
+    // little endian accumulator, reversed at the end
+    let mut out: Vec<u8> = Vec::new();
+    for c in addr.bytes() {
+        let mut carry = BASE58_ALPHABET.iter().position(|&a| a == c)? as u32;
+        for b in out.iter_mut() {
+            carry += (*b as u32) * 58;
+            *b = (carry & 0xff) as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            out.push((carry & 0xff) as u8);
+            carry >>= 8;
+        }
+    }
+
+    let zeros = addr.bytes().take_while(|&c| c == b'1').count();
+    out.extend(std::iter::repeat(0).take(zeros));
+    out.reverse();
+
+    Some(out)
+}
@@ -0,0 +1,107 @@
+//! Signs bridge actions with a validator key and prints the `HandleMsg` to submit.
+//!
+//! Usage: `validator <keypair.json> <action.json>`
+//!
+//! The keypair file holds the base64 ed25519 seed and public key:
+//! `{"secret_key": "...", "public_key": "..."}`
+//!
+//! The action is either a message to sign as is
+//! `{"msg": {"chain_nonce": 1, "sc_addr": "secret1...", "action_id": 7, "msg": {"set_pause": true}}}`
+//! or the log of a peer bridge's freeze or withdraw, signed under an action id allocated on the destination
+//! `{"event": {"chain_nonce": 1, "sc_addr": "secret1...", "action_id": 7, "log": [{"key": "...", "value": "..."}], "route": {"code_hash": "...", "contract": null, "burner": null}}}`
+use std::env::args;
+use std::fs::read;
+use std::process::exit;
+
+use cosmwasm_std::{Binary, HumanAddr, LogAttribute, StdError, StdResult};
+use ed25519_compact::{KeyPair, Seed};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use bridge::decode::decode_log;
+use bridge::msg::{HandleMsg, SignableMsg, Sig, ValidatorInfo};
+use bridge::relay::{relayed_action, Route};
+use bridge::signing::signing_digest;
+
+#[derive(Deserialize)]
+struct KeypairFile {
+    secret_key: Binary,
+    public_key: Binary,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Action {
+    Msg {
+        chain_nonce: u64,
+        sc_addr: HumanAddr,
+        action_id: u128,
+        msg: SignableMsg,
+    },
+    Event {
+        chain_nonce: u64,
+        sc_addr: HumanAddr,
+        action_id: u128,
+        log: Vec<LogAttribute>,
+        route: Route,
+    },
+}
+
+fn read_json<T: DeserializeOwned>(path: &str) -> StdResult<T> {
+    let raw = read(path).map_err(|e| StdError::generic_err(format!("reading {}: {}", path, e)))?;
+
+    serde_json_wasm::from_slice(&raw).map_err(|e| StdError::parse_err(std::any::type_name::<T>(), e))
+}
+
+fn load_keypair(path: &str) -> StdResult<KeyPair> {
+    let file: KeypairFile = read_json(path)?;
+    let seed: [u8; 32] = file.secret_key
+        .as_slice()
+        .try_into()
+        .map_err(|_| StdError::generic_err("secret_key must be a 32 byte seed"))?;
+
+    let kp = KeyPair::from_seed(Seed::new(seed));
+    if kp.pk[..] != file.public_key.as_slice()[..] {
+        return Err(StdError::generic_err("public_key doesn't belong to secret_key"));
+    }
+
+    Ok(kp)
+}
+
+fn sign(kp: &KeyPair, path: &str) -> StdResult<HandleMsg> {
+    let (chain_nonce, sc_addr, action_id, msg) = match read_json(path)? {
+        Action::Msg { chain_nonce, sc_addr, action_id, msg } => (chain_nonce, sc_addr, action_id, msg),
+        Action::Event { chain_nonce, sc_addr, action_id, log, route } => {
+            let (_, msg) = relayed_action(&decode_log(&log)?, &route)?;
+            (chain_nonce, sc_addr, action_id, msg)
+        }
+    };
+
+    let action = msg.action_bytes(&sc_addr, action_id, chain_nonce)?;
+    let digest = signing_digest(msg.context(), &action);
+    let sig = Sig(*kp.sk.sign(&digest, None));
+
+    Ok(msg.into_handle_msg(ValidatorInfo { action_id, sig }))
+}
+
+fn run(args: &[String]) -> StdResult<String> {
+    let (keypair, action) = match args {
+        [keypair, action] => (keypair, action),
+        _ => return Err(StdError::generic_err("usage: validator <keypair.json> <action.json>")),
+    };
+
+    let msg = sign(&load_keypair(keypair)?, action)?;
+
+    serde_json_wasm::to_string(&msg).map_err(|e| StdError::serialize_err("HandleMsg", e))
+}
+
+fn main() {
+    let args: Vec<String> = args().skip(1).collect();
+    match run(&args) {
+        Ok(msg) => println!("{}", msg),
+        Err(e) => {
+            eprintln!("{}", e);
+            exit(1);
+        }
+    }
+}
@@ -0,0 +1,343 @@
+use cosmwasm_std::{
+    from_binary, to_vec, Binary, CanonicalAddr, Coin, CosmosMsg, Empty, HumanAddr, Querier, QueryRequest, StdError,
+    StdResult, Uint128, WasmMsg, WasmQuery,
+};
+use secret_toolkit::permit::Permit;
+use secret_toolkit::snip721::ViewerInfo;
+use secret_toolkit::utils::space_pad;
+use serde::de::DeserializeOwned;
+
+use crate::contract::{BLOCK_SIZE, FEE_DENOM};
+use crate::msg::{
+    ActionConsumedResponse, ActionSpace, ActionsConsumedResponse, ChainNonceResponse, ChainResponse, ChainsResponse,
+    CompletionReceipt, ConfigResponse, ConsumedActionsResponse, Direction, EventCntResponse, ForeignToken,
+    ForeignTokenResponse, GroupKeyResponse, GuardiansResponse, HandleMsg, HistoryResponse, InCustodyResponse,
+    OutboundResponse, PausedResponse, QueryMsg, QueryWithPermit, RateLimitResponse, ReceiptResponse, SignableMsg,
+    SigningPayloadResponse, ValidatorInfo, VerifySignatureResponse, ViewingKeyResponse, WhitelistResponse,
+    WhitelistedResponse, WrappedTokenResponse,
+};
+use crate::state::OutboundAction;
+
+/// Tokens of a single collection sent to another chain, by freezing originals or burning wrapped ones
+#[derive(Clone, Debug, PartialEq)]
+pub struct Outbound {
+    pub collection: HumanAddr,
+    pub code_hash: String,
+    pub token_ids: Vec<String>,
+    pub to: String,
+    pub chain_nonce: u64,
+    pub viewer: Option<ViewerInfo>,
+    /// Collection minting the wrapped tokens on the destination, only used when freezing
+    pub minter: String,
+}
+
+impl Outbound {
+    pub fn new(collection: HumanAddr, code_hash: String, to: String, chain_nonce: u64) -> Self {
+        Self {
+            collection,
+            code_hash,
+            token_ids: Vec::new(),
+            to,
+            chain_nonce,
+            viewer: None,
+            minter: String::new(),
+        }
+    }
+
+    pub fn token(mut self, token_id: impl Into<String>) -> Self {
+        self.token_ids.push(token_id.into());
+        self
+    }
+
+    pub fn tokens<I: IntoIterator<Item = String>>(mut self, token_ids: I) -> Self {
+        self.token_ids.extend(token_ids);
+        self
+    }
+
+    pub fn viewer(mut self, viewer: ViewerInfo) -> Self {
+        self.viewer = Some(viewer);
+        self
+    }
+
+    pub fn minter(mut self, minter: impl Into<String>) -> Self {
+        self.minter = minter.into();
+        self
+    }
+
+    fn single(&self) -> StdResult<Option<String>> {
+        match self.token_ids.as_slice() {
+            [] => Err(StdError::generic_err("no tokens to send")),
+            [token_id] => Ok(Some(token_id.clone())),
+            _ => Ok(None),
+        }
+    }
+
+    /// `FreezeNft`, or `FreezeNftBatch` for several tokens
+    pub fn freeze_msg(&self) -> StdResult<HandleMsg> {
+        let msg = match self.single()? {
+            Some(token_id) => HandleMsg::FreezeNft {
+                contract: self.collection.clone(),
+                contract_hash: self.code_hash.clone(),
+                token_id,
+                viewer: self.viewer.clone(),
+                to: self.to.clone(),
+                chain_nonce: self.chain_nonce,
+                minter: self.minter.clone(),
+            },
+            None => HandleMsg::FreezeNftBatch {
+                contract: self.collection.clone(),
+                contract_hash: self.code_hash.clone(),
+                token_ids: self.token_ids.clone(),
+                viewer: self.viewer.clone(),
+                to: self.to.clone(),
+                chain_nonce: self.chain_nonce,
+                minter: self.minter.clone(),
+            },
+        };
+
+        Ok(msg)
+    }
+
+    /// `WithdrawNft`, or `WithdrawNftBatch` for several tokens
+    pub fn withdraw_msg(&self) -> StdResult<HandleMsg> {
+        let msg = match self.single()? {
+            Some(token_id) => HandleMsg::WithdrawNft {
+                burner: self.collection.clone(),
+                burner_hash: self.code_hash.clone(),
+                token_id,
+                viewer: self.viewer.clone(),
+                to: self.to.clone(),
+                chain_nonce: self.chain_nonce,
+            },
+            None => HandleMsg::WithdrawNftBatch {
+                burner: self.collection.clone(),
+                burner_hash: self.code_hash.clone(),
+                token_ids: self.token_ids.clone(),
+                viewer: self.viewer.clone(),
+                to: self.to.clone(),
+                chain_nonce: self.chain_nonce,
+            },
+        };
+
+        Ok(msg)
+    }
+}
+
+/// A deployed bridge.
+/// Builds the messages sent to it, padded like the bridge pads its own, and parses its responses
+#[derive(Clone, Debug, PartialEq)]
+pub struct BridgeClient {
+    pub address: HumanAddr,
+    pub code_hash: String,
+}
+
+impl BridgeClient {
+    pub fn new(address: HumanAddr, code_hash: String) -> Self {
+        Self { address, code_hash }
+    }
+
+    fn padded(msg: &impl serde::Serialize) -> StdResult<Binary> {
+        let mut msg = to_vec(msg)?;
+        space_pad(&mut msg, BLOCK_SIZE);
+
+        Ok(Binary(msg))
+    }
+
+    /// Execute `msg` on the bridge sending `send` along
+    pub fn execute(&self, msg: &HandleMsg, send: Vec<Coin>) -> StdResult<CosmosMsg> {
+        Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: self.address.clone(),
+            callback_code_hash: self.code_hash.clone(),
+            msg: Self::padded(msg)?,
+            send,
+        }))
+    }
+
+    /// Submit a validator signed message
+    pub fn signed(&self, msg: SignableMsg, info: ValidatorInfo) -> StdResult<CosmosMsg> {
+        self.execute(&msg.into_handle_msg(info), vec![])
+    }
+
+    /// Claim a transfer to the sender with the `ClaimTransfer[Batch]` signature validators issued for it
+    pub fn claim(&self, msg: SignableMsg, info: ValidatorInfo) -> StdResult<CosmosMsg> {
+        match msg {
+            SignableMsg::ClaimTransfer(_) | SignableMsg::ClaimTransferBatch(_) => self.signed(msg, info),
+            _ => Err(StdError::generic_err("only transfers can be claimed")),
+        }
+    }
+
+    /// Freeze originals in the bridge paying `fee` in the bridge's fee denom
+    pub fn freeze(&self, outbound: &Outbound, fee: Uint128) -> StdResult<CosmosMsg> {
+        self.execute(&outbound.freeze_msg()?, vec![Coin { denom: FEE_DENOM.to_string(), amount: fee }])
+    }
+
+    /// Burn wrapped tokens paying `fee` in the bridge's fee denom
+    pub fn withdraw(&self, outbound: &Outbound, fee: Uint128) -> StdResult<CosmosMsg> {
+        self.execute(&outbound.withdraw_msg()?, vec![Coin { denom: FEE_DENOM.to_string(), amount: fee }])
+    }
+
+    pub fn emergency_pause(&self) -> StdResult<CosmosMsg> {
+        self.execute(&HandleMsg::EmergencyPause {}, vec![])
+    }
+
+    pub fn execute_rescue(&self, action_id: u128) -> StdResult<CosmosMsg> {
+        self.execute(&HandleMsg::ExecuteRescue { action_id }, vec![])
+    }
+
+    pub fn set_viewing_key(&self, key: String) -> StdResult<CosmosMsg> {
+        self.execute(&HandleMsg::SetViewingKey { key, padding: None }, vec![])
+    }
+
+    /// The generated key is in the response data, see `BridgeClient::viewing_key_data`
+    pub fn create_viewing_key(&self, entropy: String) -> StdResult<CosmosMsg> {
+        self.execute(&HandleMsg::CreateViewingKey { entropy, padding: None }, vec![])
+    }
+
+    pub fn revoke_permit(&self, permit_name: String) -> StdResult<CosmosMsg> {
+        self.execute(&HandleMsg::RevokePermit { permit_name, padding: None }, vec![])
+    }
+
+    /// Key set by a `CreateViewingKey` response
+    pub fn viewing_key_data(data: &Option<Binary>) -> StdResult<String> {
+        let data = data.as_ref().ok_or_else(|| StdError::not_found("response data"))?;
+        let res: ViewingKeyResponse = from_binary(data)?;
+
+        Ok(res.key)
+    }
+
+    /// Receipt returned by a `Validate*` response
+    pub fn receipt_data(data: &Option<Binary>) -> StdResult<CompletionReceipt> {
+        let data = data.as_ref().ok_or_else(|| StdError::not_found("response data"))?;
+
+        from_binary(data)
+    }
+
+    /// Smart query of the bridge
+    pub fn query_request(&self, msg: &QueryMsg) -> StdResult<QueryRequest<Empty>> {
+        Ok(QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: self.address.clone(),
+            callback_code_hash: self.code_hash.clone(),
+            msg: Self::padded(msg)?,
+        }))
+    }
+
+    pub fn query<Q: Querier, T: DeserializeOwned>(&self, querier: &Q, msg: &QueryMsg) -> StdResult<T> {
+        querier.query(&self.query_request(msg)?)
+    }
+
+    pub fn config<Q: Querier>(&self, querier: &Q) -> StdResult<ConfigResponse> {
+        self.query(querier, &QueryMsg::GetConfig)
+    }
+
+    pub fn paused<Q: Querier>(&self, querier: &Q) -> StdResult<bool> {
+        let res: PausedResponse = self.query(querier, &QueryMsg::GetPaused)?;
+        Ok(res.paused)
+    }
+
+    pub fn group_key<Q: Querier>(&self, querier: &Q) -> StdResult<[u8; 32]> {
+        let res: GroupKeyResponse = self.query(querier, &QueryMsg::GetGroupKey)?;
+        Ok(res.group_key)
+    }
+
+    pub fn chain_nonce<Q: Querier>(&self, querier: &Q) -> StdResult<u64> {
+        let res: ChainNonceResponse = self.query(querier, &QueryMsg::GetChainNonce)?;
+        Ok(res.chain_nonce)
+    }
+
+    pub fn event_cnt<Q: Querier>(&self, querier: &Q) -> StdResult<u128> {
+        let res: EventCntResponse = self.query(querier, &QueryMsg::GetEventCnt)?;
+        Ok(res.event_cnt.0)
+    }
+
+    pub fn guardians<Q: Querier>(&self, querier: &Q) -> StdResult<Vec<HumanAddr>> {
+        let res: GuardiansResponse = self.query(querier, &QueryMsg::GetGuardians)?;
+        Ok(res.guardians)
+    }
+
+    pub fn whitelisted<Q: Querier>(&self, querier: &Q, addr: CanonicalAddr) -> StdResult<bool> {
+        let res: WhitelistedResponse = self.query(querier, &QueryMsg::GetWhitelisted { addr })?;
+        Ok(res.whitelisted)
+    }
+
+    pub fn list_whitelisted<Q: Querier>(&self, querier: &Q, start: Option<u32>, limit: Option<u32>) -> StdResult<Vec<HumanAddr>> {
+        let res: WhitelistResponse = self.query(querier, &QueryMsg::ListWhitelisted { start, limit })?;
+        Ok(res.contracts)
+    }
+
+    pub fn chain<Q: Querier>(&self, querier: &Q, chain_nonce: u64) -> StdResult<ChainResponse> {
+        self.query(querier, &QueryMsg::GetChain { chain_nonce })
+    }
+
+    pub fn chains<Q: Querier>(&self, querier: &Q) -> StdResult<ChainsResponse> {
+        self.query(querier, &QueryMsg::ListChains)
+    }
+
+    pub fn rate_limit<Q: Querier>(&self, querier: &Q, direction: Direction, collection: Option<String>, height: u64) -> StdResult<RateLimitResponse> {
+        self.query(querier, &QueryMsg::GetRateLimit { direction, collection, height })
+    }
+
+    pub fn in_custody<Q: Querier>(&self, querier: &Q, contract: HumanAddr, token_id: String) -> StdResult<bool> {
+        let res: InCustodyResponse = self.query(querier, &QueryMsg::GetInCustody { contract, token_id })?;
+        Ok(res.in_custody)
+    }
+
+    pub fn receipt<Q: Querier>(&self, querier: &Q, source_chain_nonce: u64, action_id: u128) -> StdResult<Option<CompletionReceipt>> {
+        let res: ReceiptResponse = self.query(querier, &QueryMsg::GetReceipt { source_chain_nonce, action_id })?;
+        Ok(res.receipt)
+    }
+
+    pub fn outbound<Q: Querier>(&self, querier: &Q, action_id: u128) -> StdResult<Option<OutboundAction>> {
+        let res: OutboundResponse = self.query(querier, &QueryMsg::GetOutbound { action_id })?;
+        Ok(res.outbound)
+    }
+
+    pub fn wrapped_token<Q: Querier>(&self, querier: &Q, source_chain_nonce: u64, contract: String, token_id: String) -> StdResult<WrappedTokenResponse> {
+        self.query(querier, &QueryMsg::GetWrappedToken { source_chain_nonce, contract, token_id })
+    }
+
+    pub fn foreign_token<Q: Querier>(&self, querier: &Q, contract: HumanAddr, token_id: String) -> StdResult<Option<ForeignToken>> {
+        let res: ForeignTokenResponse = self.query(querier, &QueryMsg::GetForeignToken { contract, token_id })?;
+        Ok(res.foreign)
+    }
+
+    pub fn history<Q: Querier>(&self, querier: &Q, address: HumanAddr, key: String, start: Option<u32>, limit: Option<u32>) -> StdResult<HistoryResponse> {
+        self.query(querier, &QueryMsg::GetHistory { address, key, start, limit })
+    }
+
+    pub fn history_with_permit<Q: Querier>(&self, querier: &Q, permit: Permit, start: Option<u32>, limit: Option<u32>) -> StdResult<HistoryResponse> {
+        self.query(querier, &QueryMsg::WithPermit { permit, query: QueryWithPermit::GetHistory { start, limit } })
+    }
+
+    pub fn signing_payload<Q: Querier>(&self, querier: &Q, msg: SignableMsg, action_id: u128) -> StdResult<SigningPayloadResponse> {
+        self.query(querier, &QueryMsg::GetSigningPayload { msg, action_id })
+    }
+
+    pub fn verify_signature<Q: Querier>(&self, querier: &Q, msg: HandleMsg) -> StdResult<VerifySignatureResponse> {
+        self.query(querier, &QueryMsg::VerifySignature { msg })
+    }
+
+    pub fn action_consumed<Q: Querier>(&self, querier: &Q, space: ActionSpace, action: u128) -> StdResult<bool> {
+        let msg = match space {
+            ActionSpace::Validate => QueryMsg::GetActionConsumed { action },
+            ActionSpace::Config => QueryMsg::GetActionConfigConsumed { action },
+        };
+        let res: ActionConsumedResponse = self.query(querier, &msg)?;
+
+        Ok(res.consumed)
+    }
+
+    pub fn actions_consumed<Q: Querier>(&self, querier: &Q, space: ActionSpace, actions: Vec<u128>) -> StdResult<ActionsConsumedResponse> {
+        self.query(querier, &QueryMsg::GetActionsConsumed { space, actions })
+    }
+
+    pub fn list_actions_consumed<Q: Querier>(
+        &self,
+        querier: &Q,
+        space: ActionSpace,
+        start: u128,
+        end: Option<u128>,
+        limit: Option<u32>,
+    ) -> StdResult<ConsumedActionsResponse> {
+        self.query(querier, &QueryMsg::ListActionsConsumed { space, start, end, limit })
+    }
+}
@@ -1,36 +1,75 @@
 use borsh::BorshSerialize;
 use cosmwasm_std::{
     debug_print, to_binary, Api, Binary, Env, Extern, HandleResponse, InitResponse, Querier,
-    StdError, StdResult, Storage, BankMsg, LogAttribute, HumanAddr, Uint128, CanonicalAddr,
+    StdError, StdResult, Storage, BankMsg, CosmosMsg, LogAttribute, HumanAddr, Uint128, CanonicalAddr, Coin,
 };
 use cosmwasm_storage::Singleton;
 use secret_toolkit::snip721::{nft_dossier_query, transfer_nft_msg, mint_nft_msg, Metadata, burn_nft_msg, Transfer, batch_transfer_nft_msg, Burn, batch_burn_nft_msg};
-use sha2::{Sha512, Digest};
-
-use crate::events::{BridgeEventInfo, TransferSnip721, UnfreezeSnip721, TransferInfo, TransferSnip721Batch, UnfreezeSnip721Batch};
-use crate::msg::{HandleMsg, InitMsg, QueryMsg, BridgeAction, ValidatorInfo};
-use crate::state::{config, config_read, State, action_read, action_config_read, action, action_config, whitelisted_read, whitelisted};
-use ed25519_compact::{PublicKey, Signature};
+use secret_toolkit::permit::{validate, RevokedPermits, TokenPermissions};
+use secret_toolkit::snip20::transfer_msg as snip20_transfer_msg;
+use sha2::{Sha256, Digest};
+
+use crate::events::{BridgeEventInfo, TransferSnip721, UnfreezeSnip721, TransferInfo, TransferSnip721Batch, UnfreezeSnip721Batch, GuardianPause, Refund, Rescue, RescueQueued, ExecutedEventInfo, ActionCompleted, action_attr};
+use crate::msg::{HandleMsg, InitMsg, QueryMsg, BridgeAction, ValidatorInfo, Direction, RateLimitResponse, CompletionKind, CompletionReceipt, ReceiptToken, HistoryEntry, HistoryKind, HistoryStatus, HistoryResponse, QueryWithPermit, ViewingKeyResponse, ConfigResponse, PausedResponse, GroupKeyResponse, ChainNonceResponse, EventCntResponse, GuardiansResponse, WhitelistedResponse, WhitelistResponse, ChainResponse, ChainsResponse, ReceiptResponse, InCustodyResponse, ActionConsumedResponse, SigningPayloadResponse, VerifySignatureResponse, ActionSpace, ActionStatus, ActionsConsumedResponse, ConsumedActionsResponse, OutboundResponse, ClaimTransfer, ClaimTransferBatch, MintArgs, ForeignToken, WrappedTokenResponse, ForeignTokenResponse, RescueAsset};
+use crate::address::validate_address;
+use crate::encryption::encrypt_log;
+use crate::error::{ContractError, ContractResult};
+use crate::rate_limit::{consume, remaining};
+use crate::signing::{action_bytes, signing_digest, verify_digest};
+use crate::viewing_key::{hash_key, new_key, require_viewing_key};
+use crate::state::{config, config_read, State, action, action_config, action_consumed, whitelisted_read, add_whitelist, remove_whitelist, whitelist_page, ChainInfo, chain, chain_read, chain_nonces, chain_nonces_read, rate_limit, rate_limit_read, rate_usage, custody, custody_read, pending_rescue, pending_rescue_read, PendingRescue, prng_seed, prng_seed_read, encrypted_cnt, receipt, receipt_read, contract_addr, contract_addr_read, viewing_key, push_history, history_page, history, history_ref, history_ref_read, HistoryRef, outbound, outbound_read, OutboundAction, wrapped_minter, wrapped_minter_read, foreign_token, foreign_token_read, PREFIX_REVOKED_PERMITS};
 
 // TODO: confirm if this value is correct
-const BLOCK_SIZE: usize = 256;
+/// Messages to and from the bridge are padded to a multiple of this
+pub const BLOCK_SIZE: usize = 256;
+
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const FEE_DENOM: &str = "SCRT";
+
+const DEFAULT_PAGE_LIMIT: u32 = 30;
+const MAX_PAGE_LIMIT: u32 = 100;
+const MAX_ACTION_SCAN: u128 = 1000;
+/// Most tokens a single outbound batch may carry
+const MAX_BATCH_SIZE: usize = 100;
 
 pub fn init<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     msg: InitMsg,
 ) -> StdResult<InitResponse> {
+    // The remaining seed inputs are public, without entropy event encryption keys could be recomputed
+    if msg.entropy.as_slice().is_empty() {
+        return Err(StdError::generic_err("entropy is required"));
+    }
+
+    let guardians = msg.guardians
+        .iter()
+        .map(|g| deps.api.canonical_address(g))
+        .collect::<StdResult<Vec<_>>>()?;
+
     let state = State {
         group_key: msg.group_key,
         chain_nonce: msg.chain_nonce,
         event_cnt: Uint128(0),
-        paused: false
+        paused: false,
+        guardians,
+        rescue_delay: msg.rescue_delay,
+        event_key: msg.event_key
     };
 
     config(&mut deps.storage).save(&state)?;
 
+    let mut hasher = Sha256::new();
+    hasher.update(msg.entropy.as_slice());
+    hasher.update(env.block.height.to_be_bytes());
+    hasher.update(env.message.sender.0.as_bytes());
+    let mut seed = [0u8; 32];
+    seed.copy_from_slice(&hasher.finalize());
+    prng_seed(&mut deps.storage).save(&seed)?;
+    contract_addr(&mut deps.storage).save(&env.contract.address)?;
+
     for contract in msg.whitelist {
-        whitelisted(&mut deps.storage, contract).save(&true)?;
+        add_whitelist(&mut deps.storage, contract)?;
     }
 
     debug_print!("Contract was initialized by {}", env.message.sender);
@@ -45,25 +84,18 @@ fn require_sig_i<S: Storage>(
     info: ValidatorInfo,
     context: &[u8],
     inner: impl BorshSerialize,
-) -> StdResult<()> {
+) -> ContractResult<()> {
     if store.load().unwrap_or(false) {
-        return Err(StdError::generic_err("duplicate action"));
+        return Err(ContractError::DuplicateAction { action_id: info.action_id });
     }
     store.save(&true)?;
 
     let action = BridgeAction::new(env, info.action_id, state.chain_nonce, inner)?;
-    let raw_act = action.try_to_vec().map_err(|e|
-        StdError::serialize_err("borsh", e.to_string())
-    )?;
+    let hash = signing_digest(context, &action_bytes(&action)?);
 
-    let mut hasher = Sha512::new();
-    hasher.update(context);
-    hasher.update(raw_act);
-    let hash = hasher.finalize();
-
-    let sig = Signature::new(info.sig.0);
-    let key = PublicKey::new(state.group_key);
-    key.verify(hash, &sig).map_err(|_| StdError::unauthorized())?;
+    if !verify_digest(&state.group_key, &hash, &info.sig) {
+        return Err(ContractError::InvalidSignature {});
+    }
 
     Ok(())
 }
@@ -75,7 +107,7 @@ fn require_sig<S: Storage>(
     info: ValidatorInfo,
     context: &[u8],
     inner: impl BorshSerialize,
-) -> StdResult<()> {
+) -> ContractResult<()> {
     require_sig_i(action(storage, info.action_id), env, state, info, context, inner)
 }
 
@@ -86,33 +118,350 @@ fn require_sig_config<S: Storage>(
     info: ValidatorInfo,
     context: &[u8],
     inner: impl BorshSerialize,
-) -> StdResult<()> {
+) -> ContractResult<()> {
     require_sig_i(action_config(storage, info.action_id), env, state, info, context, inner)
 }
 
+fn require_destination<S: Storage>(
+    storage: &S,
+    state: &State,
+    chain_nonce: u64,
+    to: &str
+) -> ContractResult<()> {
+    if chain_nonce == state.chain_nonce {
+        return Err(ContractError::SameChain {});
+    }
+
+    let info = chain_read(storage, chain_nonce)
+        .may_load()?
+        .ok_or_else(|| ContractError::UnsupportedChain { chain_nonce })?;
+    if !validate_address(&info.address_format, to) {
+        return Err(ContractError::InvalidAddress { address: to.to_string() });
+    }
+
+    Ok(())
+}
+
+/// Claims may only be sent by the recipient of the transfer
+fn require_recipient<A: Api>(api: &A, env: &Env, to: &str) -> ContractResult<()> {
+    if api.canonical_address(&HumanAddr(to.to_string()))? != api.canonical_address(&env.message.sender)? {
+        return Err(ContractError::NotRecipient { recipient: to.to_string() });
+    }
+
+    Ok(())
+}
+
+/// Persist the receipt of an executed foreign action,
+/// returning its completion event and the response data
+fn complete_action<S: Storage>(
+    storage: &mut S,
+    completion: CompletionReceipt
+) -> StdResult<(LogAttribute, Binary)> {
+    receipt(storage, completion.source_chain_nonce, completion.action_id).save(&completion)?;
+
+    let data = to_binary(&completion)?;
+    let event: LogAttribute = ActionCompleted {
+        event_version: ActionCompleted::VERSION,
+        receipt: completion
+    }.try_into()?;
+
+    Ok((event, data))
+}
+
+/// Append to the owner's history, remembering where outbound actions were recorded
+/// so their status can be updated later
+fn record_history<S: Storage>(
+    storage: &mut S,
+    owner: CanonicalAddr,
+    entry: HistoryEntry
+) -> StdResult<()> {
+    let idx = push_history(storage, &owner, &entry)?;
+    if entry.status == HistoryStatus::Pending {
+        history_ref(storage, entry.action_id).save(&HistoryRef { owner, idx })?;
+    }
+
+    Ok(())
+}
+
+fn history_response<S: Storage>(
+    storage: &S,
+    owner: &CanonicalAddr,
+    start: Option<u32>,
+    limit: Option<u32>
+) -> StdResult<HistoryResponse> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT);
+    let (entries, total) = history_page(storage, owner, start.unwrap_or(0), limit)?;
+
+    Ok(HistoryResponse { entries, total })
+}
+
+/// The outbound action `action_id` if a refund of `kind` may still give it back
+fn refundable<S: Storage>(storage: &S, action_id: u128, kind: HistoryKind) -> ContractResult<OutboundAction> {
+    let sent = outbound_read(storage, action_id)
+        .may_load()?
+        .ok_or(ContractError::UnknownAction { action_id })?;
+    if sent.refunded {
+        return Err(ContractError::AlreadyRefunded { action_id });
+    }
+    if sent.kind != kind {
+        return Err(ContractError::WrongRefund { action_id });
+    }
+
+    Ok(sent)
+}
+
+/// Flag the outbound action and the sender's history entry as refunded
+fn mark_refunded<S: Storage>(storage: &mut S, action_id: u128, mut sent: OutboundAction) -> StdResult<()> {
+    sent.refunded = true;
+    outbound(storage, action_id).save(&sent)?;
+
+    if let Some(HistoryRef { owner, idx }) = history_ref_read(storage, action_id).may_load()? {
+        let mut entry = history(storage, &owner, idx).load()?;
+        entry.status = HistoryStatus::Refunded;
+        history(storage, &owner, idx).save(&entry)?;
+    }
+
+    Ok(())
+}
+
+/// Pay the fee of a refunded action back to its sender
+fn fee_refund(env: &Env, sent: &OutboundAction) -> CosmosMsg {
+    BankMsg::Send {
+        from_address: env.contract.address.clone(),
+        to_address: sent.sender.clone(),
+        amount: vec![Coin { denom: FEE_DENOM.to_string(), amount: Uint128(sent.fee) }]
+    }.into()
+}
+
+/// Id to mint the wrapped token of `args` under, recording the foreign token it stands for.
+/// A foreign token is only ever wrapped by one collection
+fn map_wrapped<S: Storage, A: Api>(
+    storage: &mut S,
+    api: &A,
+    source_chain_nonce: u64,
+    args: &MintArgs
+) -> ContractResult<String> {
+    let foreign = ForeignToken {
+        chain_nonce: source_chain_nonce,
+        contract: args.contract.clone(),
+        token_id: args.token_id.clone()
+    };
+    let token_id = foreign.wrapped_id()?;
+    let minter = api.canonical_address(&HumanAddr(args.minter.clone()))?;
+    match wrapped_minter_read(storage, &token_id).may_load()? {
+        Some(contract) if contract != minter => {
+            return Err(ContractError::WrappedElsewhere { token_id, contract: api.human_address(&contract)? });
+        }
+        _ => {}
+    }
+    wrapped_minter(storage, &token_id).save(&minter)?;
+    foreign_token(storage, &minter, &token_id).save(&foreign)?;
+
+    Ok(token_id)
+}
+
+/// Foreign identity of a wrapped token being withdrawn, it can only go back to its own chain
+fn withdrawn_foreign<S: Storage, A: Api>(
+    storage: &S,
+    api: &A,
+    burner: &HumanAddr,
+    token_id: &str,
+    chain_nonce: u64
+) -> ContractResult<Option<ForeignToken>> {
+    let foreign = foreign_token_read(storage, &api.canonical_address(burner)?, token_id).may_load()?;
+    match &foreign {
+        Some(f) if f.chain_nonce != chain_nonce => Err(ContractError::WrongChain {
+            token_id: token_id.to_string(),
+            chain_nonce: f.chain_nonce
+        }),
+        _ => Ok(foreign)
+    }
+}
+
+/// Transfer of a rescued asset along with its event.
+/// Tokens in custody for a foreign chain are refused unless forced, forcing releases them
+fn rescue_asset<S: Storage, A: Api>(
+    storage: &mut S,
+    api: &A,
+    env: &Env,
+    asset: RescueAsset
+) -> ContractResult<(CosmosMsg, LogAttribute)> {
+    let (msg, event) = match asset {
+        RescueAsset::Nft(inner) => {
+            let collection = api.canonical_address(&HumanAddr(inner.contract.clone()))?;
+            if custody_read(storage, &collection, &inner.token_id).may_load()?.is_some() {
+                if !inner.force {
+                    return Err(ContractError::InCustody { contract: inner.contract, token_id: inner.token_id });
+                }
+                custody(storage, &collection, &inner.token_id).remove();
+            }
+
+            let transfer = transfer_nft_msg(
+                HumanAddr(inner.to.clone()),
+                inner.token_id.clone(),
+                None,
+                None,
+                BLOCK_SIZE,
+                inner.contract_hash.clone(),
+                HumanAddr(inner.contract.clone())
+            )?;
+
+            (transfer, Rescue {
+                event_version: Rescue::VERSION,
+                contract: Some(HumanAddr(inner.contract)),
+                token_id: Some(inner.token_id),
+                denom: None,
+                amount: None,
+                to: HumanAddr(inner.to),
+                forced: inner.force
+            })
+        }
+        RescueAsset::Snip20(inner) => {
+            let transfer = snip20_transfer_msg(
+                HumanAddr(inner.to.clone()),
+                Uint128(inner.amount),
+                None,
+                None,
+                BLOCK_SIZE,
+                inner.contract_hash.clone(),
+                HumanAddr(inner.contract.clone())
+            )?;
+
+            (transfer, Rescue {
+                event_version: Rescue::VERSION,
+                contract: Some(HumanAddr(inner.contract)),
+                token_id: None,
+                denom: None,
+                amount: Some(inner.amount),
+                to: HumanAddr(inner.to),
+                forced: false
+            })
+        }
+        RescueAsset::Native(inner) => {
+            let bank_msg = BankMsg::Send {
+                from_address: env.contract.address.clone(),
+                to_address: HumanAddr(inner.to.clone()),
+                amount: vec![Coin { denom: inner.denom.clone(), amount: Uint128(inner.amount) }]
+            };
+
+            (bank_msg.into(), Rescue {
+                event_version: Rescue::VERSION,
+                contract: None,
+                token_id: None,
+                denom: Some(inner.denom),
+                amount: Some(inner.amount),
+                to: HumanAddr(inner.to),
+                forced: false
+            })
+        }
+    };
+
+    Ok((msg, event.try_into()?))
+}
+
+/// Carry out a signed rescue, or queue it for `ExecuteRescue` while a rescue delay is set
+fn rescue<S: Storage, A: Api>(
+    storage: &mut S,
+    api: &A,
+    env: &Env,
+    state: &State,
+    action_id: u128,
+    action: &str,
+    asset: RescueAsset
+) -> ContractResult<HandleResponse> {
+    if state.rescue_delay == 0 {
+        let (transfer, event) = rescue_asset(storage, api, env, asset)?;
+        return Ok(HandleResponse {
+            messages: vec![transfer],
+            log: vec![action_attr(action), event],
+            data: None
+        });
+    }
+
+    let ready_at = env.block.height.saturating_add(state.rescue_delay);
+    pending_rescue(storage, action_id).save(&PendingRescue { ready_at, asset: asset.clone() })?;
+
+    let log: Vec<LogAttribute> = vec![
+        action_attr(action),
+        RescueQueued {
+            event_version: RescueQueued::VERSION,
+            action_id,
+            ready_at,
+            asset
+        }.try_into()?
+    ];
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log,
+        data: None
+    })
+}
+
 fn action_id<S: Storage>(
     store: &mut Singleton<S, State>,
     state: &mut State
-) -> StdResult<u128> {
+) -> ContractResult<u128> {
     let cnt = state.event_cnt.0;
     let ret = Ok(cnt);
-    state.event_cnt = Uint128(cnt+1);
+    state.event_cnt = Uint128(cnt.checked_add(1).ok_or(ContractError::ActionIdOverflow {})?);
     store.save(&state)?;
 
     return ret;
 }
 
+fn require_batch_size(token_ids: &[String]) -> ContractResult<()> {
+    if token_ids.is_empty() || token_ids.len() > MAX_BATCH_SIZE {
+        return Err(ContractError::BatchSize { len: token_ids.len(), max: MAX_BATCH_SIZE });
+    }
+
+    Ok(())
+}
+
 pub fn handle<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     msg: HandleMsg,
-) -> StdResult<HandleResponse> {
+) -> ContractResult<HandleResponse> {
+    let mut res = handle_i(deps, env, msg)?;
+
+    // Log attributes are public, seal events to the validator set when configured
+    if let Some(key) = config_read(&deps.storage).load()?.event_key {
+        let seed = prng_seed_read(&deps.storage).load()?;
+        let mut cnt_store = encrypted_cnt(&mut deps.storage);
+        let cnt = cnt_store.may_load()?.unwrap_or(0);
+        cnt_store.save(&(cnt + 1))?;
+
+        res.log = encrypt_log(&seed, cnt, &key, res.log)?;
+    }
+
+    Ok(res)
+}
+
+fn handle_i<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    msg: HandleMsg,
+) -> ContractResult<HandleResponse> {
     let Extern { storage, api, querier } = deps;
     let mut store = config(storage);
     let mut state = store.load()?;
 
+    // A claim completes the same action as a relayer push, it's only signed under its own context
+    let (msg, claim_context) = match msg {
+        HandleMsg::ClaimTransfer { info, inner: ClaimTransfer(inner) } => {
+            require_recipient(&*api, &env, &inner.to)?;
+            (HandleMsg::ValidateTransferNft { info, inner }, Some(b"ClaimTransfer" as &[u8]))
+        }
+        HandleMsg::ClaimTransferBatch { info, inner: ClaimTransferBatch(inner) } => {
+            require_recipient(&*api, &env, &inner.to)?;
+            (HandleMsg::ValidateTransferNftBatch { info, inner }, Some(b"ClaimTransferBatch" as &[u8]))
+        }
+        msg => (msg, None)
+    };
+
     let require_unpause = |state: &State| if state.paused {
-        return Err(StdError::unauthorized())
+        return Err(ContractError::Paused {})
     } else {
         Ok(())
     };
@@ -120,14 +469,14 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
     let tx_fee = |env: &Env| {
         env.message.sent_funds
             .iter()
-            .find(|c| c.denom == "SCRT")
+            .find(|c| c.denom == FEE_DENOM)
             .map(|c| c.amount.u128())
-            .ok_or_else(|| StdError::generic_err("TX Fees required!"))
+            .ok_or_else(|| ContractError::FeeRequired { denom: FEE_DENOM.to_string() })
     };
 
     let require_whitelist = |store: &S,addr: &HumanAddr| {
         if !whitelisted_read(store, api.canonical_address(addr)?).load().unwrap_or(false) {
-            Err(StdError::unauthorized())
+            Err(ContractError::NotWhitelisted { contract: addr.clone() })
         } else {
             Ok(())
         }
@@ -152,7 +501,7 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
         HandleMsg::WithdrawFees { info, inner } => {
             require_unpause(&state)?;
             let contract_addr = env.contract.address.clone();
-            let bal = querier.query_balance(&contract_addr, "SCRT")?;
+            let bal = querier.query_balance(&contract_addr, FEE_DENOM)?;
 
             let bank_msg = BankMsg::Send {
                 from_address: contract_addr,
@@ -170,11 +519,166 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
         },
         HandleMsg::WhitelistNft { info, inner } => {
             require_unpause(&state)?;
-            whitelisted(storage, CanonicalAddr(Binary(inner.0.clone()))).save(&true)?;
+            add_whitelist(storage, CanonicalAddr(Binary(inner.0.clone())))?;
             require_sig_config(storage, &env, state, info, b"WhitelistNft", inner)?;
         }
+        HandleMsg::UnwhitelistNft { info, inner } => {
+            // Revoking must stay possible while paused, e.g. for a compromised collection
+            remove_whitelist(storage, CanonicalAddr(Binary(inner.0.clone())))?;
+            require_sig_config(storage, &env, state, info, b"UnwhitelistNft", inner)?;
+        }
+        HandleMsg::WhitelistNftBatch { info, inner } => {
+            if !inner.add.is_empty() {
+                require_unpause(&state)?;
+            }
+
+            for addr in inner.add.iter() {
+                add_whitelist(storage, CanonicalAddr(Binary(addr.clone())))?;
+            }
+            for addr in inner.remove.iter() {
+                remove_whitelist(storage, CanonicalAddr(Binary(addr.clone())))?;
+            }
+
+            require_sig_config(storage, &env, state, info, b"WhitelistNftBatch", inner)?;
+        }
+        HandleMsg::SetGuardians { info, inner } => {
+            state.guardians = inner.0
+                .iter()
+                .map(|g| api.canonical_address(&HumanAddr(g.clone())))
+                .collect::<StdResult<Vec<_>>>()?;
+            store.save(&state)?;
+
+            require_sig_config(storage, &env, state, info, b"SetGuardians", inner)?;
+        }
+        HandleMsg::RegisterChain { info, inner } => {
+            require_unpause(&state)?;
+
+            let mut nonces = chain_nonces_read(storage).may_load()?.unwrap_or_default();
+            if !nonces.contains(&inner.chain_nonce) {
+                nonces.push(inner.chain_nonce);
+                chain_nonces(storage).save(&nonces)?;
+            }
+            chain(storage, inner.chain_nonce).save(&ChainInfo {
+                chain_nonce: inner.chain_nonce,
+                name: inner.name.clone(),
+                address_format: inner.address_format.clone()
+            })?;
+
+            require_sig_config(storage, &env, state, info, b"RegisterChain", inner)?;
+        }
+        HandleMsg::UnregisterChain { info, inner } => {
+            let mut nonces = chain_nonces_read(storage).may_load()?.unwrap_or_default();
+            nonces.retain(|n| *n != inner.0);
+            chain_nonces(storage).save(&nonces)?;
+            chain(storage, inner.0).remove();
+
+            require_sig_config(storage, &env, state, info, b"UnregisterChain", inner)?;
+        }
+        HandleMsg::SetRateLimit { info, inner } => {
+            // Keyed like `consume` does, whatever spelling of the address was signed
+            let collection = inner.collection
+                .as_ref()
+                .map(|c| api.canonical_address(&HumanAddr(c.clone())))
+                .transpose()?;
+            match &inner.limit {
+                Some(limit) => rate_limit(storage, &inner.direction, collection.as_ref()).save(limit)?,
+                None => rate_limit(storage, &inner.direction, collection.as_ref()).remove()
+            }
+            // Slot boundaries depend on the window length, start counting afresh
+            rate_usage(storage, &inner.direction, collection.as_ref()).remove();
+
+            require_sig_config(storage, &env, state, info, b"SetRateLimit", inner)?;
+        }
+        HandleMsg::RescueNft { info, inner } => {
+            let res = rescue(storage, &*api, &env, &state, info.action_id, "rescue_nft", RescueAsset::Nft(inner.clone()))?;
+            require_sig_config(storage, &env, state, info, b"RescueNft", inner)?;
+
+            return Ok(res);
+        }
+        HandleMsg::RescueSnip20 { info, inner } => {
+            let res = rescue(storage, &*api, &env, &state, info.action_id, "rescue_snip20", RescueAsset::Snip20(inner.clone()))?;
+            require_sig_config(storage, &env, state, info, b"RescueSnip20", inner)?;
+
+            return Ok(res);
+        }
+        HandleMsg::RescueNative { info, inner } => {
+            let res = rescue(storage, &*api, &env, &state, info.action_id, "rescue_native", RescueAsset::Native(inner.clone()))?;
+            require_sig_config(storage, &env, state, info, b"RescueNative", inner)?;
+
+            return Ok(res);
+        }
+        HandleMsg::ExecuteRescue { action_id } => {
+            // Pausing holds queued rescues, guardians can stop one during its delay
+            require_unpause(&state)?;
+            let pending = pending_rescue_read(storage, action_id)
+                .may_load()?
+                .ok_or(ContractError::UnknownRescue { action_id })?;
+            if env.block.height < pending.ready_at {
+                return Err(ContractError::RescueTimelocked { ready_at: pending.ready_at });
+            }
+            pending_rescue(storage, action_id).remove();
+
+            let (transfer, event) = rescue_asset(storage, &*api, &env, pending.asset)?;
+
+            return Ok(HandleResponse {
+                messages: vec![transfer],
+                log: vec![action_attr("execute_rescue"), event],
+                data: None
+            });
+        }
+        HandleMsg::SetEventKey { info, inner } => {
+            state.event_key = inner.0;
+            store.save(&state)?;
+
+            require_sig_config(storage, &env, state, info, b"SetEventKey", inner)?;
+        }
+        HandleMsg::SetViewingKey { key, .. } => {
+            let owner = api.canonical_address(&env.message.sender)?;
+            viewing_key(storage, &owner).save(&hash_key(&key))?;
+        }
+        HandleMsg::CreateViewingKey { entropy, .. } => {
+            let owner = api.canonical_address(&env.message.sender)?;
+            let key = new_key(&prng_seed_read(storage).load()?, &env, &entropy);
+            viewing_key(storage, &owner).save(&hash_key(&key))?;
+
+            return Ok(HandleResponse {
+                messages: vec![],
+                log: vec![],
+                data: Some(to_binary(&ViewingKeyResponse { key })?)
+            });
+        }
+        HandleMsg::RevokePermit { permit_name, .. } => {
+            RevokedPermits::revoke_permit(storage, PREFIX_REVOKED_PERMITS, &env.message.sender.0, &permit_name);
+        }
+        HandleMsg::EmergencyPause {} => {
+            // Guardians may only ever pause, unpausing still requires a group signature
+            let sender = api.canonical_address(&env.message.sender)?;
+            if !state.guardians.contains(&sender) {
+                return Err(ContractError::NotGuardian {});
+            }
+
+            state.paused = true;
+            store.save(&state)?;
+
+            let log: Vec<LogAttribute> = vec![
+                action_attr("emergency_pause"),
+                GuardianPause {
+                    event_version: GuardianPause::VERSION,
+                    guardian: env.message.sender.clone()
+                }.try_into()?
+            ];
+
+            return Ok(HandleResponse {
+                messages: vec![],
+                log,
+                data: None
+            });
+        }
         HandleMsg::ValidateUnfreezeNft { info, inner } => {
             require_unpause(&state)?;
+            let collection = api.canonical_address(&HumanAddr(inner.unfreeze_args.contract.clone()))?;
+            consume(storage, Direction::Inbound, &collection, 1, env.block.height)?;
+            custody(storage, &collection, &inner.unfreeze_args.token_id).remove();
 
             let transfer = transfer_nft_msg(
                 HumanAddr(inner.to.clone()),
@@ -186,16 +690,50 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
                 HumanAddr(inner.unfreeze_args.contract.clone())
             )?;
 
+            let tokens: Vec<ReceiptToken> = vec![ReceiptToken {
+                contract: HumanAddr(inner.unfreeze_args.contract.clone()),
+                token_id: inner.unfreeze_args.token_id.clone()
+            }];
+            record_history(storage, api.canonical_address(&HumanAddr(inner.to.clone()))?, HistoryEntry {
+                action_id: info.action_id,
+                kind: HistoryKind::ReceiveUnfreeze,
+                status: HistoryStatus::Completed,
+                chain_nonce: inner.source_chain_nonce,
+                counterparty: None,
+                tokens: tokens.clone(),
+                block_height: env.block.height
+            })?;
+
+            let (completed, data) = complete_action(storage, CompletionReceipt {
+                kind: CompletionKind::Unfreeze,
+                source_chain_nonce: inner.source_chain_nonce,
+                action_id: info.action_id,
+                to: HumanAddr(inner.to.clone()),
+                tokens,
+                block_height: env.block.height
+            })?;
+
+            let log: Vec<LogAttribute> = vec![
+                action_attr("validate_unfreeze_nft"),
+                ExecutedEventInfo::new(&env, info.action_id, state.chain_nonce).try_into()?,
+                completed
+            ];
+
             require_sig(storage, &env, state, info, b"ValidateUnfreezeNft", inner)?;
 
             return Ok(HandleResponse {
                 messages: vec![transfer],
-                log: vec![],
-                data: None
+                log,
+                data: Some(data)
             });
         }
         HandleMsg::ValidateUnfreezeNftBatch { info, inner } => {
             require_unpause(&state)?;
+            for a in inner.unfreeze_args.iter() {
+                let collection = api.canonical_address(&HumanAddr(a.contract.clone()))?;
+                consume(storage, Direction::Inbound, &collection, 1, env.block.height)?;
+                custody(storage, &collection, &a.token_id).remove();
+            }
 
             let messages = inner.unfreeze_args.clone().into_iter().map(|a| transfer_nft_msg(
                 HumanAddr(inner.to.clone()),
@@ -207,19 +745,51 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
                 HumanAddr(a.contract)
             )).collect::<Result<Vec<_>, _>>()?;
 
+            let tokens: Vec<ReceiptToken> = inner.unfreeze_args.iter().map(|a| ReceiptToken {
+                contract: HumanAddr(a.contract.clone()),
+                token_id: a.token_id.clone()
+            }).collect();
+            record_history(storage, api.canonical_address(&HumanAddr(inner.to.clone()))?, HistoryEntry {
+                action_id: info.action_id,
+                kind: HistoryKind::ReceiveUnfreeze,
+                status: HistoryStatus::Completed,
+                chain_nonce: inner.source_chain_nonce,
+                counterparty: None,
+                tokens: tokens.clone(),
+                block_height: env.block.height
+            })?;
+
+            let (completed, data) = complete_action(storage, CompletionReceipt {
+                kind: CompletionKind::Unfreeze,
+                source_chain_nonce: inner.source_chain_nonce,
+                action_id: info.action_id,
+                to: HumanAddr(inner.to.clone()),
+                tokens,
+                block_height: env.block.height
+            })?;
+
+            let log: Vec<LogAttribute> = vec![
+                action_attr("validate_unfreeze_nft_batch"),
+                ExecutedEventInfo::new(&env, info.action_id, state.chain_nonce).try_into()?,
+                completed
+            ];
+
             require_sig(storage, &env, state, info, b"ValidateUnfreezeNftBatch", inner)?;
 
             return Ok(HandleResponse {
                 messages,
-                log: vec![],
-                data: None
+                log,
+                data: Some(data)
             });
         }
         HandleMsg::ValidateTransferNft { info, inner } => {
             require_unpause(&state)?;
+            let collection = api.canonical_address(&HumanAddr(inner.mint_args.minter.clone()))?;
+            consume(storage, Direction::Inbound, &collection, 1, env.block.height)?;
+            let token_id = map_wrapped(storage, &*api, inner.source_chain_nonce, &inner.mint_args)?;
 
             let mint = mint_nft_msg(
-                Some(inner.mint_args.token_id.clone()),
+                Some(token_id.clone()),
                 Some(HumanAddr(inner.to.clone())),
                 Some(Metadata {
                     token_uri: Some(inner.mint_args.token_uri.clone()),
@@ -233,19 +803,56 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
                 HumanAddr(inner.mint_args.minter.clone())
             )?;
 
-            require_sig(storage, &env, state, info, b"ValidateTransferNft", inner)?;
+            let tokens: Vec<ReceiptToken> = vec![ReceiptToken {
+                contract: HumanAddr(inner.mint_args.minter.clone()),
+                token_id
+            }];
+            record_history(storage, api.canonical_address(&HumanAddr(inner.to.clone()))?, HistoryEntry {
+                action_id: info.action_id,
+                kind: HistoryKind::ReceiveTransfer,
+                status: HistoryStatus::Completed,
+                chain_nonce: inner.source_chain_nonce,
+                counterparty: None,
+                tokens: tokens.clone(),
+                block_height: env.block.height
+            })?;
+
+            let (completed, data) = complete_action(storage, CompletionReceipt {
+                kind: CompletionKind::Transfer,
+                source_chain_nonce: inner.source_chain_nonce,
+                action_id: info.action_id,
+                to: HumanAddr(inner.to.clone()),
+                tokens,
+                block_height: env.block.height
+            })?;
+
+            let log: Vec<LogAttribute> = vec![
+                action_attr("validate_transfer_nft"),
+                ExecutedEventInfo::new(&env, info.action_id, state.chain_nonce).try_into()?,
+                completed
+            ];
+
+            require_sig(storage, &env, state, info, claim_context.unwrap_or(b"ValidateTransferNft"), inner)?;
 
             return Ok(HandleResponse {
                 messages: vec![mint],
-                log: vec![],
-                data: None
+                log,
+                data: Some(data)
             });
         }
         HandleMsg::ValidateTransferNftBatch { info, inner } => {
             require_unpause(&state)?;
-
-            let messages = inner.mint_args.clone().into_iter().map(|a| mint_nft_msg(
-                Some(a.token_id),
+            for a in inner.mint_args.iter() {
+                let collection = api.canonical_address(&HumanAddr(a.minter.clone()))?;
+                consume(storage, Direction::Inbound, &collection, 1, env.block.height)?;
+            }
+            let token_ids = inner.mint_args
+                .iter()
+                .map(|a| map_wrapped(storage, &*api, inner.source_chain_nonce, a))
+                .collect::<ContractResult<Vec<_>>>()?;
+
+            let messages = inner.mint_args.clone().into_iter().zip(token_ids.iter()).map(|(a, tok)| mint_nft_msg(
+                Some(tok.clone()),
                 Some(HumanAddr(inner.to.clone())),
                 Some(Metadata {
                     token_uri: Some(a.token_uri),
@@ -259,23 +866,171 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
                 HumanAddr(a.minter)
             )).collect::<Result<Vec<_>, _>>()?;
 
-            require_sig(storage, &env, state, info, b"ValidateTransferNftBatch", inner)?;
+            let tokens: Vec<ReceiptToken> = inner.mint_args.iter().zip(token_ids).map(|(a, token_id)| ReceiptToken {
+                contract: HumanAddr(a.minter.clone()),
+                token_id
+            }).collect();
+            record_history(storage, api.canonical_address(&HumanAddr(inner.to.clone()))?, HistoryEntry {
+                action_id: info.action_id,
+                kind: HistoryKind::ReceiveTransfer,
+                status: HistoryStatus::Completed,
+                chain_nonce: inner.source_chain_nonce,
+                counterparty: None,
+                tokens: tokens.clone(),
+                block_height: env.block.height
+            })?;
+
+            let (completed, data) = complete_action(storage, CompletionReceipt {
+                kind: CompletionKind::Transfer,
+                source_chain_nonce: inner.source_chain_nonce,
+                action_id: info.action_id,
+                to: HumanAddr(inner.to.clone()),
+                tokens,
+                block_height: env.block.height
+            })?;
+
+            let log: Vec<LogAttribute> = vec![
+                action_attr("validate_transfer_nft_batch"),
+                ExecutedEventInfo::new(&env, info.action_id, state.chain_nonce).try_into()?,
+                completed
+            ];
+
+            require_sig(storage, &env, state, info, claim_context.unwrap_or(b"ValidateTransferNftBatch"), inner)?;
 
             return Ok(HandleResponse {
                 messages,
-                log: vec![],
+                log,
+                data: Some(data)
+            });
+
+        }
+        HandleMsg::RefundFreeze { info, inner } => {
+            require_unpause(&state)?;
+            let sent = refundable(storage, inner.action_id, HistoryKind::Freeze)?;
+            let collection = api.canonical_address(&sent.contract)?;
+            // a token unfrozen since may be held for a later freeze, which this refund mustn't release
+            for tok in sent.token_ids.iter() {
+                if custody_read(storage, &collection, tok).may_load()? != Some(inner.action_id) {
+                    return Err(ContractError::NotHeld { action_id: inner.action_id, token_id: tok.clone() });
+                }
+                custody(storage, &collection, tok).remove();
+            }
+
+            let mut messages = vec![batch_transfer_nft_msg(
+                vec![Transfer { token_ids: sent.token_ids.clone(), memo: None, recipient: sent.sender.clone() }],
+                None,
+                BLOCK_SIZE,
+                sent.contract_hash.clone(),
+                sent.contract.clone()
+            )?];
+            let fee = if inner.refund_fee && sent.fee > 0 {
+                messages.push(fee_refund(&env, &sent));
+                Some(sent.fee)
+            } else {
+                None
+            };
+
+            let log: Vec<LogAttribute> = vec![
+                action_attr("refund_freeze"),
+                ExecutedEventInfo::new(&env, info.action_id, state.chain_nonce).try_into()?,
+                Refund {
+                    event_version: Refund::VERSION,
+                    action_id: inner.action_id,
+                    contract: sent.contract.clone(),
+                    token_ids: sent.token_ids.clone(),
+                    to: sent.sender.clone(),
+                    fee
+                }.try_into()?
+            ];
+
+            mark_refunded(storage, inner.action_id, sent)?;
+            require_sig(storage, &env, state, info, b"RefundFreeze", inner)?;
+
+            return Ok(HandleResponse {
+                messages,
+                log,
                 data: None
             });
+        }
+        HandleMsg::RefundWithdraw { info, inner } => {
+            require_unpause(&state)?;
+            let sent = refundable(storage, inner.action_id, HistoryKind::Withdraw)?;
+
+            let mut messages = sent.token_ids.iter().zip(sent.token_uris.iter()).map(|(tok, uri)| mint_nft_msg(
+                Some(tok.clone()),
+                Some(sent.sender.clone()),
+                Some(Metadata {
+                    token_uri: Some(uri.clone()),
+                    extension: None
+                }),
+                None,
+                None,
+                None,
+                BLOCK_SIZE,
+                sent.contract_hash.clone(),
+                sent.contract.clone()
+            )).collect::<Result<Vec<_>, _>>()?;
+            let fee = if inner.refund_fee && sent.fee > 0 {
+                messages.push(fee_refund(&env, &sent));
+                Some(sent.fee)
+            } else {
+                None
+            };
+
+            let log: Vec<LogAttribute> = vec![
+                action_attr("refund_withdraw"),
+                ExecutedEventInfo::new(&env, info.action_id, state.chain_nonce).try_into()?,
+                Refund {
+                    event_version: Refund::VERSION,
+                    action_id: inner.action_id,
+                    contract: sent.contract.clone(),
+                    token_ids: sent.token_ids.clone(),
+                    to: sent.sender.clone(),
+                    fee
+                }.try_into()?
+            ];
+
+            mark_refunded(storage, inner.action_id, sent)?;
+            require_sig(storage, &env, state, info, b"RefundWithdraw", inner)?;
 
+            return Ok(HandleResponse {
+                messages,
+                log,
+                data: None
+            });
         }
         HandleMsg::FreezeNft { contract, contract_hash, token_id, viewer, to, chain_nonce, minter } => {
             require_unpause(&state)?;
             let our_addr = env.contract.address.clone();
             let act_id = action_id(&mut store, &mut state)?;
             let fee = tx_fee(&env)?;
+            require_destination(storage, &state, chain_nonce, &to)?;
+            let collection = api.canonical_address(&contract)?;
+            consume(storage, Direction::Outbound, &collection, 1, env.block.height)?;
+            custody(storage, &collection, &token_id).save(&act_id)?;
 
             require_whitelist(&storage, &contract)?;
 
+            record_history(storage, api.canonical_address(&env.message.sender)?, HistoryEntry {
+                action_id: act_id,
+                kind: HistoryKind::Freeze,
+                status: HistoryStatus::Pending,
+                chain_nonce,
+                counterparty: Some(to.clone()),
+                tokens: vec![ReceiptToken { contract: contract.clone(), token_id: token_id.clone() }],
+                block_height: env.block.height
+            })?;
+            outbound(storage, act_id).save(&OutboundAction {
+                kind: HistoryKind::Freeze,
+                sender: env.message.sender.clone(),
+                contract: contract.clone(),
+                contract_hash: contract_hash.clone(),
+                token_ids: vec![token_id.clone()],
+                token_uris: vec![],
+                fee,
+                refunded: false
+            })?;
+
 
             let nft_dat = nft_dossier_query(
                 querier,
@@ -288,9 +1043,11 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             )?;
 
             let log: Vec<LogAttribute> = vec![
-                BridgeEventInfo::new(act_id, chain_nonce, fee, to.clone())
+                action_attr("freeze_nft"),
+                BridgeEventInfo::new(&env, act_id, state.chain_nonce, chain_nonce, fee, to.clone())
                     .try_into()?,
                 TransferSnip721 {
+                    event_version: TransferSnip721::VERSION,
                     info: TransferInfo {
                         public_metadata: nft_dat.public_metadata,
                         private_metadata: nft_dat.private_metadata,
@@ -320,12 +1077,39 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
         },
         HandleMsg::FreezeNftBatch { contract, contract_hash, token_ids, viewer, to, chain_nonce, minter } => {
             require_unpause(&state)?;
+            require_batch_size(&token_ids)?;
             let our_addr = env.contract.address.clone();
             let act_id = action_id(&mut store, &mut state)?;
             let fee = tx_fee(&env)?;
+            require_destination(storage, &state, chain_nonce, &to)?;
+            let collection = api.canonical_address(&contract)?;
+            consume(storage, Direction::Outbound, &collection, token_ids.len() as u64, env.block.height)?;
+            for tok in token_ids.iter() {
+                custody(storage, &collection, tok).save(&act_id)?;
+            }
 
             require_whitelist(&storage, &contract)?;
 
+            record_history(storage, api.canonical_address(&env.message.sender)?, HistoryEntry {
+                action_id: act_id,
+                kind: HistoryKind::Freeze,
+                status: HistoryStatus::Pending,
+                chain_nonce,
+                counterparty: Some(to.clone()),
+                tokens: token_ids.iter().map(|t| ReceiptToken { contract: contract.clone(), token_id: t.clone() }).collect(),
+                block_height: env.block.height
+            })?;
+            outbound(storage, act_id).save(&OutboundAction {
+                kind: HistoryKind::Freeze,
+                sender: env.message.sender.clone(),
+                contract: contract.clone(),
+                contract_hash: contract_hash.clone(),
+                token_ids: token_ids.clone(),
+                token_uris: vec![],
+                fee,
+                refunded: false
+            })?;
+
             let transfers = Transfer { token_ids: token_ids.clone(), memo: None, recipient: our_addr };
             let transfer_infos = token_ids.into_iter().map(|tok| {
                 let nft_dat = nft_dossier_query(
@@ -354,8 +1138,10 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             )?;
 
             let log: Vec<LogAttribute> = vec![
-                BridgeEventInfo::new(act_id, chain_nonce, fee, to).try_into()?,
+                action_attr("freeze_nft_batch"),
+                BridgeEventInfo::new(&env, act_id, state.chain_nonce, chain_nonce, fee, to).try_into()?,
                 TransferSnip721Batch {
+                    event_version: TransferSnip721Batch::VERSION,
                     infos: transfer_infos,
                     contract_addr: contract,
                     contract_hash: contract_hash,
@@ -374,6 +1160,19 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
 
             let act_id = action_id(&mut store, &mut state)?;
             let fee = tx_fee(&env)?;
+            require_destination(storage, &state, chain_nonce, &to)?;
+            let foreign = withdrawn_foreign(storage, &*api, &burner, &token_id, chain_nonce)?;
+            let collection = api.canonical_address(&burner)?;
+            consume(storage, Direction::Outbound, &collection, 1, env.block.height)?;
+            record_history(storage, api.canonical_address(&env.message.sender)?, HistoryEntry {
+                action_id: act_id,
+                kind: HistoryKind::Withdraw,
+                status: HistoryStatus::Pending,
+                chain_nonce,
+                counterparty: Some(to.clone()),
+                tokens: vec![ReceiptToken { contract: burner.clone(), token_id: token_id.clone() }],
+                block_height: env.block.height
+            })?;
 
             let nft_dat = nft_dossier_query(
                 querier,
@@ -387,13 +1186,27 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             let token_uri = nft_dat.public_metadata
                 .map(|m| m.token_uri)
                 .flatten()
-                .ok_or_else(|| StdError::unauthorized())?;
+                .ok_or_else(|| ContractError::MissingTokenUri { token_id: token_id.clone() })?;
+            outbound(storage, act_id).save(&OutboundAction {
+                kind: HistoryKind::Withdraw,
+                sender: env.message.sender.clone(),
+                contract: burner.clone(),
+                contract_hash: burner_hash.clone(),
+                token_ids: vec![token_id.clone()],
+                token_uris: vec![token_uri.clone()],
+                fee,
+                refunded: false
+            })?;
 
             let log: Vec<LogAttribute> = vec![
-                BridgeEventInfo::new(act_id, chain_nonce, fee, to.clone())
+                action_attr("withdraw_nft"),
+                BridgeEventInfo::new(&env, act_id, state.chain_nonce, chain_nonce, fee, to.clone())
                     .try_into()?,
                 UnfreezeSnip721 {
+                    event_version: UnfreezeSnip721::VERSION,
+                    token_id: token_id.clone(),
                     token_uri: token_uri,
+                    foreign,
                     burner: burner.clone()
                 }.try_into()?
             ];
@@ -415,12 +1228,29 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
         },
         HandleMsg::WithdrawNftBatch { burner, burner_hash, token_ids, viewer, to, chain_nonce } => {
             require_unpause(&state)?;
+            require_batch_size(&token_ids)?;
 
             let act_id = action_id(&mut store, &mut state)?;
             let fee = tx_fee(&env)?;
+            require_destination(storage, &state, chain_nonce, &to)?;
+            let foreign = token_ids
+                .iter()
+                .map(|tok| withdrawn_foreign(storage, &*api, &burner, tok, chain_nonce))
+                .collect::<ContractResult<Vec<_>>>()?;
+            let collection = api.canonical_address(&burner)?;
+            consume(storage, Direction::Outbound, &collection, token_ids.len() as u64, env.block.height)?;
+            record_history(storage, api.canonical_address(&env.message.sender)?, HistoryEntry {
+                action_id: act_id,
+                kind: HistoryKind::Withdraw,
+                status: HistoryStatus::Pending,
+                chain_nonce,
+                counterparty: Some(to.clone()),
+                tokens: token_ids.iter().map(|t| ReceiptToken { contract: burner.clone(), token_id: t.clone() }).collect(),
+                block_height: env.block.height
+            })?;
 
             let burns = Burn { token_ids: token_ids.clone(), memo: None };
-            let token_uris = token_ids.into_iter().map(|tok| {
+            let token_uris = token_ids.iter().map(|tok| {
                let nft_dat = nft_dossier_query(
                     querier,
                     tok.clone(),
@@ -434,8 +1264,18 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
                 return nft_dat.public_metadata
                     .map(|m| m.token_uri)
                     .flatten()
-                    .ok_or_else(|| StdError::unauthorized());
+                    .ok_or_else(|| ContractError::MissingTokenUri { token_id: tok.clone() });
             }).collect::<Result<Vec<_>, _>>()?;
+            outbound(storage, act_id).save(&OutboundAction {
+                kind: HistoryKind::Withdraw,
+                sender: env.message.sender.clone(),
+                contract: burner.clone(),
+                contract_hash: burner_hash.clone(),
+                token_ids: token_ids.clone(),
+                token_uris: token_uris.clone(),
+                fee,
+                refunded: false
+            })?;
 
             let burn = batch_burn_nft_msg(
                 vec![burns],
@@ -446,9 +1286,13 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             )?;
 
             let log: Vec<LogAttribute> = vec![
-                BridgeEventInfo::new(act_id, chain_nonce, fee, to.clone())
+                action_attr("withdraw_nft_batch"),
+                BridgeEventInfo::new(&env, act_id, state.chain_nonce, chain_nonce, fee, to.clone())
                     .try_into()?,
                 UnfreezeSnip721Batch {
+                    event_version: UnfreezeSnip721Batch::VERSION,
+                    foreign,
+                    token_ids,
                     token_uris,
                     burner
                 }.try_into()?
@@ -465,25 +1309,195 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
     Ok(HandleResponse::default())
 }
 
+fn list_chains<S: Storage>(storage: &S) -> StdResult<Vec<ChainInfo>> {
+    chain_nonces_read(storage)
+        .may_load()?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|n| chain_read(storage, n).load())
+        .collect()
+}
+
+fn humanize<A: Api>(api: &A, addrs: &[CanonicalAddr]) -> StdResult<Vec<HumanAddr>> {
+    addrs.iter().map(|a| api.human_address(a)).collect()
+}
+
 pub fn query<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     msg: QueryMsg,
-) -> StdResult<Binary> {
+) -> ContractResult<Binary> {
     let config = config_read(&deps.storage).load()?;
 
-    return match msg {
-        QueryMsg::GetPaused => { to_binary(&config.paused) },
-        QueryMsg::GetGroupKey => { to_binary(&config.group_key) },
-        QueryMsg::GetChainNonce => { to_binary(&config.chain_nonce) },
-        QueryMsg::GetEventCnt => { to_binary(&config.event_cnt) },
+    let res = match msg {
+        QueryMsg::GetConfig => {
+            to_binary(&ConfigResponse {
+                version: CONTRACT_VERSION.to_string(),
+                paused: config.paused,
+                group_key: config.group_key,
+                chain_nonce: config.chain_nonce,
+                event_cnt: config.event_cnt,
+                guardians: humanize(&deps.api, &config.guardians)?,
+                event_key: config.event_key,
+                fee_denom: FEE_DENOM.to_string(),
+                fee_balance: deps.querier.query_balance(contract_addr_read(&deps.storage).load()?, FEE_DENOM)?.amount,
+                rescue_delay: config.rescue_delay,
+                chains: list_chains(&deps.storage)?
+            })
+        },
+        QueryMsg::GetPaused => { to_binary(&PausedResponse { paused: config.paused }) },
+        QueryMsg::GetGroupKey => { to_binary(&GroupKeyResponse { group_key: config.group_key }) },
+        QueryMsg::GetChainNonce => { to_binary(&ChainNonceResponse { chain_nonce: config.chain_nonce }) },
+        QueryMsg::GetEventCnt => { to_binary(&EventCntResponse { event_cnt: config.event_cnt }) },
+        QueryMsg::GetGuardians => {
+            to_binary(&GuardiansResponse { guardians: humanize(&deps.api, &config.guardians)? })
+        },
         QueryMsg::GetWhitelisted { addr } => {
-            to_binary(&whitelisted_read(&deps.storage, addr).load()?)
+            to_binary(&WhitelistedResponse {
+                whitelisted: whitelisted_read(&deps.storage, addr).may_load()?.unwrap_or(false)
+            })
+        }
+        QueryMsg::GetChain { chain_nonce } => {
+            to_binary(&ChainResponse { chain: chain_read(&deps.storage, chain_nonce).may_load()? })
+        }
+        QueryMsg::ListChains => {
+            to_binary(&ChainsResponse { chains: list_chains(&deps.storage)? })
+        }
+        QueryMsg::GetRateLimit { direction, collection, height } => {
+            let collection = collection
+                .map(|c| deps.api.canonical_address(&HumanAddr(c)))
+                .transpose()?;
+            to_binary(&RateLimitResponse {
+                limit: rate_limit_read(&deps.storage, &direction, collection.as_ref()).may_load()?,
+                remaining: remaining(&deps.storage, &direction, collection.as_ref(), height)?
+            })
+        }
+        QueryMsg::GetReceipt { source_chain_nonce, action_id } => {
+            to_binary(&ReceiptResponse {
+                receipt: receipt_read(&deps.storage, source_chain_nonce, action_id).may_load()?
+            })
+        }
+        QueryMsg::GetHistory { address, key, start, limit } => {
+            let owner = deps.api.canonical_address(&address)?;
+            require_viewing_key(&deps.storage, &owner, &key)?;
+
+            to_binary(&history_response(&deps.storage, &owner, start, limit)?)
+        }
+        QueryMsg::WithPermit { permit, query } => {
+            let our_addr = contract_addr_read(&deps.storage).load()?;
+            let account = validate(deps, PREFIX_REVOKED_PERMITS, &permit, our_addr, None)?;
+            let owner = deps.api.canonical_address(&HumanAddr(account))?;
+
+            match query {
+                QueryWithPermit::GetHistory { start, limit } => {
+                    if !permit.check_permission(&TokenPermissions::History) {
+                        return Err(ContractError::PermitNotAllowed {});
+                    }
+
+                    to_binary(&history_response(&deps.storage, &owner, start, limit)?)
+                }
+            }
+        }
+        QueryMsg::GetSigningPayload { msg, action_id } => {
+            let our_addr = contract_addr_read(&deps.storage).load()?;
+            let action = msg.action_bytes(&our_addr, action_id, config.chain_nonce)?;
+
+            to_binary(&SigningPayloadResponse {
+                context: Binary(msg.context().to_vec()),
+                digest: Binary(signing_digest(msg.context(), &action)),
+                action: Binary(action)
+            })
+        }
+        QueryMsg::VerifySignature { msg } => {
+            let (info, msg) = msg.into_signed()
+                .ok_or_else(|| ContractError::NotSigned {})?;
+            let our_addr = contract_addr_read(&deps.storage).load()?;
+            let action = msg.action_bytes(&our_addr, info.action_id, config.chain_nonce)?;
+            let digest = signing_digest(msg.context(), &action);
+
+            let space = if msg.is_config() { ActionSpace::Config } else { ActionSpace::Validate };
+            let consumed = action_consumed(&deps.storage, &space, info.action_id)?;
+
+            let reason = if consumed {
+                Some("action id already consumed")
+            } else if !verify_digest(&config.group_key, &digest, &info.sig) {
+                Some("signature doesn't match the group key over this payload")
+            } else {
+                None
+            };
+
+            to_binary(&VerifySignatureResponse {
+                valid: reason.is_none(),
+                reason: reason.map(String::from),
+                digest: Binary(digest)
+            })
+        }
+        QueryMsg::GetInCustody { contract, token_id } => {
+            let collection = deps.api.canonical_address(&contract)?;
+            to_binary(&InCustodyResponse {
+                in_custody: custody_read(&deps.storage, &collection, &token_id).may_load()?.is_some()
+            })
+        }
+        QueryMsg::ListWhitelisted { start, limit } => {
+            let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT);
+            let contracts = whitelist_page(&deps.storage, start.unwrap_or(0), limit)?;
+            to_binary(&WhitelistResponse { contracts: humanize(&deps.api, &contracts)? })
+        }
+        QueryMsg::GetOutbound { action_id } => {
+            to_binary(&OutboundResponse { outbound: outbound_read(&deps.storage, action_id).may_load()? })
+        }
+        QueryMsg::GetWrappedToken { source_chain_nonce, contract, token_id } => {
+            let token_id = ForeignToken { chain_nonce: source_chain_nonce, contract, token_id }.wrapped_id()?;
+            let contract = wrapped_minter_read(&deps.storage, &token_id)
+                .may_load()?
+                .map(|c| deps.api.human_address(&c))
+                .transpose()?;
+            to_binary(&WrappedTokenResponse { token_id, contract })
+        }
+        QueryMsg::GetForeignToken { contract, token_id } => {
+            let contract = deps.api.canonical_address(&contract)?;
+            to_binary(&ForeignTokenResponse { foreign: foreign_token_read(&deps.storage, &contract, &token_id).may_load()? })
         }
         QueryMsg::GetActionConsumed { action } => {
-            to_binary(&action_read(&deps.storage, action).load()?)
+            to_binary(&ActionConsumedResponse { consumed: action_consumed(&deps.storage, &ActionSpace::Validate, action)? })
         },
         QueryMsg::GetActionConfigConsumed { action } => {
-            to_binary(&action_config_read(&deps.storage, action).load()?)
+            to_binary(&ActionConsumedResponse { consumed: action_consumed(&deps.storage, &ActionSpace::Config, action)? })
+        }
+        QueryMsg::GetActionsConsumed { space, actions } => {
+            if actions.len() > MAX_PAGE_LIMIT as usize {
+                return Err(ContractError::TooManyIds { max: MAX_PAGE_LIMIT });
+            }
+
+            let actions = actions
+                .into_iter()
+                .map(|action| Ok(ActionStatus {
+                    action,
+                    consumed: action_consumed(&deps.storage, &space, action)?
+                }))
+                .collect::<StdResult<Vec<_>>>()?;
+            to_binary(&ActionsConsumedResponse { actions })
+        }
+        QueryMsg::ListActionsConsumed { space, start, end, limit } => {
+            let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+            let end = end.unwrap_or(u128::MAX);
+            let scan_end = end.min(start.saturating_add(MAX_ACTION_SCAN));
+
+            // ids aren't iterable, probe each one in the range instead
+            let mut actions = Vec::new();
+            let mut cur = start;
+            while cur < scan_end && actions.len() < limit {
+                if action_consumed(&deps.storage, &space, cur)? {
+                    actions.push(cur);
+                }
+                cur += 1;
+            }
+
+            to_binary(&ConsumedActionsResponse {
+                actions,
+                next: if cur < end { Some(cur) } else { None }
+            })
         }
     };
+
+    Ok(res?)
 }
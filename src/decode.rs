@@ -0,0 +1,114 @@
+use cosmwasm_std::{LogAttribute, StdError, StdResult};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::events::{
+    ActionCompleted, BridgeEventInfo, EncryptedEvent, ExecutedEventInfo, GuardianPause, Refund, Rescue, RescueQueued, TransferSnip721, TransferSnip721Batch,
+    UnfreezeSnip721, UnfreezeSnip721Batch, ACTION_KEY,
+};
+
+/// A bridge event decoded from a log attribute
+#[derive(Debug, Clone, PartialEq)]
+pub enum BridgeEvent {
+    BridgeEventInfo(BridgeEventInfo),
+    ExecutedEventInfo(ExecutedEventInfo),
+    ActionCompleted(ActionCompleted),
+    TransferSnip721(TransferSnip721),
+    TransferSnip721Batch(TransferSnip721Batch),
+    UnfreezeSnip721(UnfreezeSnip721),
+    UnfreezeSnip721Batch(UnfreezeSnip721Batch),
+    GuardianPause(GuardianPause),
+    Rescue(Rescue),
+    RescueQueued(RescueQueued),
+    Refund(Refund),
+    EncryptedEvent(EncryptedEvent),
+    /// Known event emitted by a newer contract than this decoder understands
+    Unsupported { key: String, event_version: u32, value: String },
+}
+
+/// Events of a single handle response
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DecodedLog {
+    pub action: Option<String>,
+    pub events: Vec<BridgeEvent>,
+}
+
+#[derive(Deserialize)]
+struct VersionProbe {
+    event_version: u32,
+}
+
+fn parse<T: DeserializeOwned>(value: &str) -> StdResult<T> {
+    serde_json_wasm::from_str(value)
+        .map_err(|e| StdError::parse_err(std::any::type_name::<T>(), e))
+}
+
+/// Decode a single attribute.
+/// Returns `None` for attributes that aren't bridge events, such as ones emitted by other contracts
+pub fn decode_attribute(key: &str, value: &str) -> StdResult<Option<BridgeEvent>> {
+    let version = match key {
+        BridgeEventInfo::KEY => BridgeEventInfo::VERSION,
+        ExecutedEventInfo::KEY => ExecutedEventInfo::VERSION,
+        ActionCompleted::KEY => ActionCompleted::VERSION,
+        TransferSnip721::KEY => TransferSnip721::VERSION,
+        TransferSnip721Batch::KEY => TransferSnip721Batch::VERSION,
+        UnfreezeSnip721::KEY => UnfreezeSnip721::VERSION,
+        UnfreezeSnip721Batch::KEY => UnfreezeSnip721Batch::VERSION,
+        GuardianPause::KEY => GuardianPause::VERSION,
+        Rescue::KEY => Rescue::VERSION,
+        RescueQueued::KEY => RescueQueued::VERSION,
+        Refund::KEY => Refund::VERSION,
+        EncryptedEvent::KEY => EncryptedEvent::VERSION,
+        _ => return Ok(None),
+    };
+
+    let probe: VersionProbe = parse(value)?;
+    if probe.event_version > version {
+        return Ok(Some(BridgeEvent::Unsupported {
+            key: key.to_string(),
+            event_version: probe.event_version,
+            value: value.to_string(),
+        }));
+    }
+
+    let ev = match key {
+        BridgeEventInfo::KEY => BridgeEvent::BridgeEventInfo(parse(value)?),
+        ExecutedEventInfo::KEY => BridgeEvent::ExecutedEventInfo(parse(value)?),
+        ActionCompleted::KEY => BridgeEvent::ActionCompleted(parse(value)?),
+        TransferSnip721::KEY => BridgeEvent::TransferSnip721(parse(value)?),
+        TransferSnip721Batch::KEY => BridgeEvent::TransferSnip721Batch(parse(value)?),
+        UnfreezeSnip721::KEY => BridgeEvent::UnfreezeSnip721(parse(value)?),
+        UnfreezeSnip721Batch::KEY => BridgeEvent::UnfreezeSnip721Batch(parse(value)?),
+        GuardianPause::KEY => BridgeEvent::GuardianPause(parse(value)?),
+        Rescue::KEY => BridgeEvent::Rescue(parse(value)?),
+        RescueQueued::KEY => BridgeEvent::RescueQueued(parse(value)?),
+        Refund::KEY => BridgeEvent::Refund(parse(value)?),
+        EncryptedEvent::KEY => BridgeEvent::EncryptedEvent(parse(value)?),
+        _ => unreachable!(),
+    };
+
+    Ok(Some(ev))
+}
+
+/// Decode raw `(key, value)` attributes, e.g. taken from a transaction's logs
+pub fn decode_attributes<'a, I>(attrs: I) -> StdResult<DecodedLog>
+where
+    I: IntoIterator<Item = (&'a str, &'a str)>,
+{
+    let mut decoded = DecodedLog::default();
+
+    for (key, value) in attrs {
+        if key == ACTION_KEY {
+            decoded.action = Some(value.to_string());
+        } else if let Some(ev) = decode_attribute(key, value)? {
+            decoded.events.push(ev);
+        }
+    }
+
+    Ok(decoded)
+}
+
+/// Decode the log of a `HandleResponse`
+pub fn decode_log(log: &[LogAttribute]) -> StdResult<DecodedLog> {
+    decode_attributes(log.iter().map(|a| (a.key.as_str(), a.value.as_str())))
+}
@@ -0,0 +1,117 @@
+use chacha20poly1305::aead::{Aead, NewAead};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use cosmwasm_std::{Binary, LogAttribute, StdError, StdResult};
+use sha2::{Digest, Sha256};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use crate::decode::{decode_log, BridgeEvent};
+use crate::events::{EncryptedEvent, GuardianPause, Rescue, RescueQueued, ACTION_KEY};
+
+// Every payload is sealed under a fresh ephemeral key, so a fixed nonce is never reused
+const NONCE: [u8; 12] = [0; 12];
+
+fn payload_key(shared: &[u8], ephemeral: &PublicKey, validator: &PublicKey) -> ChaCha20Poly1305 {
+    let mut hasher = Sha256::new();
+    hasher.update(shared);
+    hasher.update(ephemeral.as_bytes());
+    hasher.update(validator.as_bytes());
+
+    ChaCha20Poly1305::new(Key::from_slice(&hasher.finalize()))
+}
+
+/// Attributes left in the clear: the action, and the admin events anyone must be able to audit
+fn is_public(key: &str) -> bool {
+    matches!(key, ACTION_KEY | GuardianPause::KEY | Rescue::KEY | RescueQueued::KEY)
+}
+
+fn log_action_id(log: &[LogAttribute]) -> Option<u128> {
+    decode_log(log).ok()?.events.iter().find_map(|ev| match ev {
+        BridgeEvent::BridgeEventInfo(info) => Some(info.action_id),
+        BridgeEvent::ExecutedEventInfo(info) => Some(info.action_id),
+        _ => None,
+    })
+}
+
+/// Replace the user transfer events of `log` with a single payload encrypted to `validator_key`.
+/// The `action` attribute, admin events and the action id stay public for indexing.
+/// `seed` must be secret and `counter` unique per call, they derive the ephemeral key
+pub fn encrypt_log(
+    seed: &[u8; 32],
+    counter: u64,
+    validator_key: &[u8; 32],
+    log: Vec<LogAttribute>,
+) -> StdResult<Vec<LogAttribute>> {
+    let (public, private): (Vec<_>, Vec<_>) = log.into_iter().partition(|a| is_public(&a.key));
+    if private.is_empty() {
+        return Ok(public);
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(seed);
+    hasher.update(counter.to_be_bytes());
+    let mut ephemeral_secret = [0u8; 32];
+    ephemeral_secret.copy_from_slice(&hasher.finalize());
+
+    let ephemeral_secret = StaticSecret::from(ephemeral_secret);
+    let ephemeral = PublicKey::from(&ephemeral_secret);
+    let validator = PublicKey::from(*validator_key);
+    let shared = ephemeral_secret.diffie_hellman(&validator);
+
+    let plaintext = serde_json_wasm::to_vec(&private)
+        .map_err(|e| StdError::serialize_err("serde-json-wasm", e))?;
+    let ciphertext = payload_key(shared.as_bytes(), &ephemeral, &validator)
+        .encrypt(Nonce::from_slice(&NONCE), plaintext.as_slice())
+        .map_err(|_| StdError::generic_err("event encryption failed"))?;
+
+    let mut log = public;
+    log.push(
+        EncryptedEvent {
+            event_version: EncryptedEvent::VERSION,
+            action_id: log_action_id(&private),
+            ephemeral_key: Binary(ephemeral.as_bytes().to_vec()),
+            ciphertext: Binary(ciphertext),
+        }
+        .try_into()?,
+    );
+
+    Ok(log)
+}
+
+/// Decrypt an encrypted event with the validator set's x25519 secret,
+/// returning the original attributes
+pub fn decrypt_event(validator_secret: &[u8; 32], event: &EncryptedEvent) -> StdResult<Vec<LogAttribute>> {
+    if event.ephemeral_key.as_slice().len() != 32 {
+        return Err(StdError::generic_err("invalid ephemeral key"));
+    }
+    let mut ephemeral = [0u8; 32];
+    ephemeral.copy_from_slice(event.ephemeral_key.as_slice());
+
+    let secret = StaticSecret::from(*validator_secret);
+    let validator = PublicKey::from(&secret);
+    let ephemeral = PublicKey::from(ephemeral);
+    let shared = secret.diffie_hellman(&ephemeral);
+
+    let plaintext = payload_key(shared.as_bytes(), &ephemeral, &validator)
+        .decrypt(Nonce::from_slice(&NONCE), event.ciphertext.as_slice())
+        .map_err(|_| StdError::generic_err("event decryption failed"))?;
+
+    serde_json_wasm::from_slice(&plaintext)
+        .map_err(|e| StdError::parse_err("Vec<LogAttribute>", e))
+}
+
+/// Decrypt every encrypted event of a log, leaving other attributes in place
+pub fn decrypt_log(validator_secret: &[u8; 32], log: &[LogAttribute]) -> StdResult<Vec<LogAttribute>> {
+    let mut out = Vec::with_capacity(log.len());
+
+    for attr in log {
+        if attr.key == EncryptedEvent::KEY {
+            let event: EncryptedEvent = serde_json_wasm::from_str(&attr.value)
+                .map_err(|e| StdError::parse_err("EncryptedEvent", e))?;
+            out.extend(decrypt_event(validator_secret, &event)?);
+        } else {
+            out.push(attr.clone());
+        }
+    }
+
+    Ok(out)
+}
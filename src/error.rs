@@ -0,0 +1,135 @@
+use cosmwasm_std::{HumanAddr, StdError};
+use snafu::Snafu;
+
+/// Failures of the bridge contract.
+/// Every message is prefixed with the variant's stable code, see `ContractError::code`
+#[derive(Snafu, Debug)]
+pub enum ContractError {
+    #[snafu(display("E000 {}", source))]
+    Std { source: StdError },
+
+    #[snafu(display("E001 bridge is paused"))]
+    Paused {},
+
+    #[snafu(display("E002 action {} was already consumed", action_id))]
+    DuplicateAction { action_id: u128 },
+
+    #[snafu(display("E003 signature doesn't match the group key"))]
+    InvalidSignature {},
+
+    #[snafu(display("E004 collection {} is not whitelisted", contract))]
+    NotWhitelisted { contract: HumanAddr },
+
+    #[snafu(display("E005 tx fees in {} required", denom))]
+    FeeRequired { denom: String },
+
+    #[snafu(display("E006 sender is not a guardian"))]
+    NotGuardian {},
+
+    #[snafu(display("E007 can't bridge to the source chain"))]
+    SameChain {},
+
+    #[snafu(display("E008 unsupported destination chain {}", chain_nonce))]
+    UnsupportedChain { chain_nonce: u64 },
+
+    #[snafu(display("E009 invalid destination address {}", address))]
+    InvalidAddress { address: String },
+
+    #[snafu(display("E010 rate limit exceeded"))]
+    RateLimitExceeded {},
+
+    #[snafu(display("E011 token {} of {} is in bridge custody", token_id, contract))]
+    InCustody { contract: String, token_id: String },
+
+    #[snafu(display("E012 token {} has no public token uri", token_id))]
+    MissingTokenUri { token_id: String },
+
+    #[snafu(display("E013 wrong viewing key"))]
+    InvalidViewingKey {},
+
+    #[snafu(display("E014 permit doesn't grant this query"))]
+    PermitNotAllowed {},
+
+    #[snafu(display("E015 not a validator signed message"))]
+    NotSigned {},
+
+    #[snafu(display("E016 at most {} ids per query", max))]
+    TooManyIds { max: u32 },
+
+    #[snafu(display("E017 no rescue is queued under action {}", action_id))]
+    UnknownRescue { action_id: u128 },
+
+    #[snafu(display("E018 rescue can't be executed before block {}", ready_at))]
+    RescueTimelocked { ready_at: u64 },
+
+    #[snafu(display("E019 batch of {} tokens, expected 1 to {}", len, max))]
+    BatchSize { len: usize, max: usize },
+
+    #[snafu(display("E020 action ids are exhausted"))]
+    ActionIdOverflow {},
+
+    #[snafu(display("E021 no outbound action {}", action_id))]
+    UnknownAction { action_id: u128 },
+
+    #[snafu(display("E022 action {} was already refunded", action_id))]
+    AlreadyRefunded { action_id: u128 },
+
+    #[snafu(display("E023 action {} is refunded by a different message", action_id))]
+    WrongRefund { action_id: u128 },
+
+    #[snafu(display("E024 token {} is no longer held for action {}", token_id, action_id))]
+    NotHeld { action_id: u128, token_id: String },
+
+    #[snafu(display("E025 only {} may claim this transfer", recipient))]
+    NotRecipient { recipient: String },
+
+    #[snafu(display("E026 wrapped token {} is already minted by {}", token_id, contract))]
+    WrappedElsewhere { token_id: String, contract: HumanAddr },
+
+    #[snafu(display("E027 token {} can only be withdrawn to chain {}", token_id, chain_nonce))]
+    WrongChain { token_id: String, chain_nonce: u64 },
+}
+
+impl ContractError {
+    /// Stable code of the failure, clients should match on this rather than the message
+    pub fn code(&self) -> u16 {
+        match self {
+            ContractError::Std { .. } => 0,
+            ContractError::Paused { .. } => 1,
+            ContractError::DuplicateAction { .. } => 2,
+            ContractError::InvalidSignature { .. } => 3,
+            ContractError::NotWhitelisted { .. } => 4,
+            ContractError::FeeRequired { .. } => 5,
+            ContractError::NotGuardian { .. } => 6,
+            ContractError::SameChain { .. } => 7,
+            ContractError::UnsupportedChain { .. } => 8,
+            ContractError::InvalidAddress { .. } => 9,
+            ContractError::RateLimitExceeded { .. } => 10,
+            ContractError::InCustody { .. } => 11,
+            ContractError::MissingTokenUri { .. } => 12,
+            ContractError::InvalidViewingKey { .. } => 13,
+            ContractError::PermitNotAllowed { .. } => 14,
+            ContractError::NotSigned { .. } => 15,
+            ContractError::TooManyIds { .. } => 16,
+            ContractError::UnknownRescue { .. } => 17,
+            ContractError::RescueTimelocked { .. } => 18,
+            ContractError::BatchSize { .. } => 19,
+            ContractError::ActionIdOverflow { .. } => 20,
+            ContractError::UnknownAction { .. } => 21,
+            ContractError::AlreadyRefunded { .. } => 22,
+            ContractError::WrongRefund { .. } => 23,
+            ContractError::NotHeld { .. } => 24,
+            ContractError::NotRecipient { .. } => 25,
+            ContractError::WrappedElsewhere { .. } => 26,
+            ContractError::WrongChain { .. } => 27,
+        }
+    }
+}
+
+impl From<StdError> for ContractError {
+    fn from(source: StdError) -> Self {
+        ContractError::Std { source }
+    }
+}
+
+pub type ContractResult<T> = Result<T, ContractError>;
@@ -1,8 +1,14 @@
 use std::convert::TryInto;
 
-use cosmwasm_std::{HumanAddr, LogAttribute, log, StdError, StdResult};
+use cosmwasm_std::{Binary, Env, HumanAddr, LogAttribute, log, StdError, StdResult};
+use schemars::JsonSchema;
 use secret_toolkit::snip721::Metadata;
-use serde::Serialize;
+
+use crate::msg::{CompletionReceipt, ForeignToken, RescueAsset};
+use serde::{Deserialize, Serialize};
+
+/// Key of the attribute naming the handled message, emitted first in every event log
+pub const ACTION_KEY: &str = "action";
 
 fn to_log_attr<T: Serialize>(name: &str, e: &T) -> StdResult<LogAttribute> {
     let ser = serde_json_wasm::to_string(e)
@@ -14,73 +20,199 @@ fn to_log_attr<T: Serialize>(name: &str, e: &T) -> StdResult<LogAttribute> {
     ))
 }
 
+pub fn action_attr(action: &str) -> LogAttribute {
+    log(ACTION_KEY, action)
+}
+
+/// Each event carries its own `event_version`, bumped whenever the layout of that event changes.
+/// Fields added after version 1 are `#[serde(default)]` so events of older contracts still decode
 macro_rules! bridge_event {
-    ($ev:ident) => {
+    ($ev:ident, $key:literal, $version:literal) => {
+        impl $ev {
+            pub const KEY: &'static str = $key;
+            pub const VERSION: u32 = $version;
+        }
+
         impl TryInto<LogAttribute> for $ev {
             type Error = StdError;
 
             fn try_into(self) -> StdResult<LogAttribute> {
-                to_log_attr(stringify!($struct), &self)
+                to_log_attr($ev::KEY, &self)
             }
         }
     };
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct BridgeEventInfo {
+    pub event_version: u32,
     pub action_id: u128,
+    /// Absent (0) in version 1 events, as are `sender` and the block fields
+    #[serde(default)]
+    pub source_chain_nonce: u64,
     pub chain_nonce: u64,
     pub tx_fees: u128,
-    pub to: String
+    #[serde(default)]
+    pub sender: HumanAddr,
+    pub to: String,
+    #[serde(default)]
+    pub block_height: u64,
+    #[serde(default)]
+    pub block_time: u64
 }
-bridge_event!(BridgeEventInfo);
+bridge_event!(BridgeEventInfo, "bridge_event_info", 2);
 
 impl BridgeEventInfo {
-    pub fn new(action_id: u128, chain_nonce: u64, tx_fees: u128, to: String) -> Self {
+    pub fn new(env: &Env, action_id: u128, source_chain_nonce: u64, chain_nonce: u64, tx_fees: u128, to: String) -> Self {
         Self {
+            event_version: Self::VERSION,
             action_id,
+            source_chain_nonce,
             chain_nonce,
             tx_fees,
-            to
+            sender: env.message.sender.clone(),
+            to,
+            block_height: env.block.height,
+            block_time: env.block.time
         }
     }
 }
 
-#[derive(Debug, Serialize)]
+/// Emitted when a validator signed action is executed on this chain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ExecutedEventInfo {
+    pub event_version: u32,
+    pub action_id: u128,
+    pub chain_nonce: u64,
+    pub executor: HumanAddr,
+    pub block_height: u64,
+    pub block_time: u64
+}
+bridge_event!(ExecutedEventInfo, "executed_event_info", 1);
+
+impl ExecutedEventInfo {
+    pub fn new(env: &Env, action_id: u128, chain_nonce: u64) -> Self {
+        Self {
+            event_version: Self::VERSION,
+            action_id,
+            chain_nonce,
+            executor: env.message.sender.clone(),
+            block_height: env.block.height,
+            block_time: env.block.time
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ActionCompleted {
+    pub event_version: u32,
+    pub receipt: CompletionReceipt
+}
+bridge_event!(ActionCompleted, "action_completed", 1);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct TransferSnip721 {
+    pub event_version: u32,
     pub contract_addr: HumanAddr,
     pub contract_hash: String,
     pub mint_with: String,
     pub info: TransferInfo
 }
-bridge_event!(TransferSnip721);
+bridge_event!(TransferSnip721, "transfer_snip721", 1);
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct TransferInfo {
     pub public_metadata: Option<Metadata>,
     pub private_metadata: Option<Metadata>,
     pub token_id: String
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct TransferSnip721Batch {
+   pub event_version: u32,
    pub infos: Vec<TransferInfo>,
    pub contract_addr: HumanAddr,
    pub contract_hash: String,
    pub mint_with: String
 }
-bridge_event!(TransferSnip721Batch);
+bridge_event!(TransferSnip721Batch, "transfer_snip721_batch", 1);
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct UnfreezeSnip721 {
+    pub event_version: u32,
+    /// Empty in version 1 events
+    #[serde(default)]
+    pub token_id: String,
     pub token_uri: String,
+    /// Token to unfreeze on its original chain, unknown for tokens the bridge didn't mint
+    #[serde(default)]
+    pub foreign: Option<ForeignToken>,
     pub burner: HumanAddr
 }
-bridge_event!(UnfreezeSnip721);
+bridge_event!(UnfreezeSnip721, "unfreeze_snip721", 3);
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct UnfreezeSnip721Batch {
+    pub event_version: u32,
+    /// Empty in version 1 events
+    #[serde(default)]
+    pub token_ids: Vec<String>,
     pub token_uris: Vec<String>,
+    /// Foreign identity of each of `token_ids`, as in `UnfreezeSnip721`
+    #[serde(default)]
+    pub foreign: Vec<Option<ForeignToken>>,
     pub burner: HumanAddr
 }
-bridge_event!(UnfreezeSnip721Batch);
+bridge_event!(UnfreezeSnip721Batch, "unfreeze_snip721_batch", 3);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct GuardianPause {
+    pub event_version: u32,
+    pub guardian: HumanAddr
+}
+bridge_event!(GuardianPause, "guardian_pause", 1);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Rescue {
+    pub event_version: u32,
+    pub contract: Option<HumanAddr>,
+    pub token_id: Option<String>,
+    pub denom: Option<String>,
+    pub amount: Option<u128>,
+    pub to: HumanAddr,
+    pub forced: bool
+}
+bridge_event!(Rescue, "rescue", 1);
+
+/// Emitted when a signed rescue has to wait out the rescue delay, `ExecuteRescue` moves the asset from `ready_at` on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RescueQueued {
+    pub event_version: u32,
+    pub action_id: u128,
+    pub ready_at: u64,
+    pub asset: RescueAsset
+}
+bridge_event!(RescueQueued, "rescue_queued", 1);
+
+/// Emitted when a failed outbound action is given back to its sender
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Refund {
+    pub event_version: u32,
+    pub action_id: u128,
+    pub contract: HumanAddr,
+    pub token_ids: Vec<String>,
+    pub to: HumanAddr,
+    /// Fee paid back along with the tokens, if any
+    pub fee: Option<u128>
+}
+bridge_event!(Refund, "refund", 1);
+
+/// User transfer events of a response sealed to the validator encryption key, see `encryption::decrypt_event`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct EncryptedEvent {
+    pub event_version: u32,
+    pub action_id: Option<u128>,
+    pub ephemeral_key: Binary,
+    pub ciphertext: Binary
+}
+bridge_event!(EncryptedEvent, "encrypted_event", 1);
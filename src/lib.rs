@@ -1,8 +1,23 @@
+pub mod address;
+#[cfg(all(feature = "client", not(target_arch = "wasm32")))]
+pub mod client;
 pub mod contract;
+pub mod decode;
+pub mod encryption;
+pub mod error;
 pub mod msg;
+pub mod rate_limit;
+#[cfg(any(test, feature = "validator"))]
+pub mod relay;
+pub mod signing;
 pub mod state;
+pub mod viewing_key;
 pub mod events;
 
+#[cfg(test)]
+mod mock_snip721;
+#[cfg(test)]
+mod mock_relayer;
 #[cfg(test)]
 mod tests;
 
@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+
+use cosmwasm_std::testing::{mock_env, MockApi};
+use cosmwasm_std::{Binary, Coin, Env, Extern, HandleResponse, HumanAddr, LogAttribute, Uint128};
+use ed25519_dalek::{ExpandedSecretKey, Keypair};
+
+use crate::contract;
+use crate::decode::decode_log;
+use crate::error::ContractResult;
+use crate::mock_snip721::{handle_and_execute, snip721_dependencies, Snip721Deps};
+use crate::msg::{
+    AddressFormat, HandleMsg, InitMsg, RegisterChain, SignableMsg, Sig, ValidatorInfo, WhitelistNft,
+};
+use crate::relay::{relayed_action, PeerAction, Route};
+use crate::signing::signing_digest;
+
+/// A bridge instance with the SNIP-721 collections of its chain
+pub struct MockChain {
+    pub deps: Snip721Deps,
+    pub bridge: HumanAddr,
+    pub chain_nonce: u64,
+    /// Next id for config messages signed by the relayer
+    config_id: u128,
+    /// Next id for relayed actions
+    validate_id: u128,
+    /// Ids relayed peer actions were signed under, so redelivery reuses them
+    relayed: HashMap<PeerAction, u128>,
+}
+
+impl MockChain {
+    /// Env of a message sent by `sender` to this chain's bridge
+    pub fn env(&self, sender: &str, sent_funds: &[Coin]) -> Env {
+        let mut env = mock_env(sender, sent_funds);
+        env.contract.address = self.bridge.clone();
+
+        env
+    }
+
+    /// Handle an unsigned message paying the tx fee
+    pub fn handle(&mut self, sender: &str, msg: HandleMsg) -> ContractResult<HandleResponse> {
+        let env = self.env(sender, &[Coin { denom: "SCRT".into(), amount: Uint128(1) }]);
+
+        handle_and_execute(&mut self.deps, env, msg)
+    }
+}
+
+/// Validator set relaying outbound actions between bridge instances
+pub struct MockRelayer {
+    pub key: Keypair,
+}
+
+impl MockRelayer {
+    fn sign(&self, chain: &MockChain, action_id: u128, msg: SignableMsg) -> HandleMsg {
+        let action = msg.action_bytes(&chain.bridge, action_id, chain.chain_nonce).unwrap();
+        let digest = signing_digest(msg.context(), &action);
+        let secret: ExpandedSecretKey = (&self.key.secret).into();
+        let sig = Sig(secret.sign(&digest, &self.key.public).to_bytes());
+
+        msg.into_handle_msg(ValidatorInfo { action_id, sig })
+    }
+
+    /// Sign a config message for `chain` under its next config action id
+    pub fn configure(&self, chain: &mut MockChain, msg: SignableMsg) -> ContractResult<HandleResponse> {
+        let msg = self.sign(chain, chain.config_id, msg);
+        chain.config_id += 1;
+        let env = chain.env("relayer", &[]);
+
+        contract::handle(&mut chain.deps, env, msg)
+    }
+
+    /// Instantiate a bridge on `chain_nonce` trusting this relayer,
+    /// with `peers` registered as destinations for bech32 "secret" addresses
+    pub fn chain(&self, chain_nonce: u64, bridge: &str, peers: &[u64]) -> MockChain {
+        // room for full bech32 addresses
+        let mut chain = MockChain {
+            deps: Extern { api: MockApi::new(64), ..snip721_dependencies() },
+            bridge: HumanAddr(bridge.into()),
+            chain_nonce,
+            config_id: 1,
+            validate_id: 1,
+            relayed: HashMap::new(),
+        };
+
+        let env = chain.env("creator", &[]);
+        contract::init(&mut chain.deps, env, InitMsg {
+            group_key: self.key.public.to_bytes(),
+            chain_nonce,
+            whitelist: Vec::new(),
+            guardians: Vec::new(),
+            rescue_delay: 0,
+            event_key: None,
+            entropy: Binary(bridge.as_bytes().to_vec()),
+        }).unwrap();
+
+        for peer in peers {
+            self.configure(&mut chain, SignableMsg::RegisterChain(RegisterChain {
+                chain_nonce: *peer,
+                name: format!("chain {}", peer),
+                address_format: AddressFormat::Bech32 { hrp: "secret".into() },
+            })).unwrap();
+        }
+
+        chain
+    }
+
+    /// Register a collection on `chain` and whitelist it for freezing
+    pub fn add_collection(&self, chain: &mut MockChain, contract: &str) {
+        chain.deps.querier.add_collection(contract, &[]);
+        let addr = chain.deps.api.canonical_address(&HumanAddr(contract.into())).unwrap();
+        self.configure(chain, SignableMsg::WhitelistNft(WhitelistNft(addr.0.0))).unwrap();
+    }
+
+    /// Register a collection on `chain` its bridge mints wrapped tokens on
+    pub fn add_wrapped_collection(&self, chain: &mut MockChain, contract: &str) {
+        let bridge = chain.bridge.clone();
+        chain.deps.querier.add_collection(contract, &[&bridge.0]);
+    }
+
+    /// Deliver the outbound action logged by a peer bridge to `to`, as validators watching the peer would
+    pub fn relay(&self, log: &[LogAttribute], route: &Route, to: &mut MockChain) -> ContractResult<HandleResponse> {
+        let (peer, msg) = relayed_action(&decode_log(log)?, route)?;
+        let action_id = match to.relayed.get(&peer) {
+            Some(action_id) => *action_id,
+            None => {
+                let action_id = to.validate_id;
+                to.validate_id += 1;
+                to.relayed.insert(peer, action_id);
+                action_id
+            }
+        };
+        let msg = self.sign(to, action_id, msg);
+        let env = to.env("relayer", &[]);
+
+        handle_and_execute(&mut to.deps, env, msg)
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{
+    from_slice, to_binary, CosmosMsg, Empty, Env, Extern, HandleResponse, HumanAddr, Querier,
+    QuerierResult, QueryRequest, StdError, StdResult, SystemError, WasmMsg, WasmQuery,
+};
+use secret_toolkit::snip721::Metadata;
+use serde::{Deserialize, Serialize};
+
+use crate::contract;
+use crate::error::ContractResult;
+use crate::msg::HandleMsg;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MockToken {
+    pub owner: HumanAddr,
+    pub public_metadata: Option<Metadata>,
+    pub private_metadata: Option<Metadata>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MockCollection {
+    pub minters: Vec<HumanAddr>,
+    pub tokens: BTreeMap<String, MockToken>,
+    /// (owner, operator) pairs allowed to transfer and burn all of owner's tokens
+    pub approvals: Vec<(HumanAddr, HumanAddr)>,
+}
+
+impl MockCollection {
+    fn require_owner_or_approved(&self, sender: &HumanAddr, token_id: &str) -> StdResult<()> {
+        let token = self.tokens
+            .get(token_id)
+            .ok_or_else(|| StdError::generic_err(format!("token {} not found", token_id)))?;
+        if &token.owner != sender && !self.approvals.contains(&(token.owner.clone(), sender.clone())) {
+            return Err(StdError::unauthorized());
+        }
+
+        Ok(())
+    }
+
+    fn transfer(&mut self, sender: &HumanAddr, recipient: HumanAddr, token_id: &str) -> StdResult<()> {
+        self.require_owner_or_approved(sender, token_id)?;
+        self.tokens.get_mut(token_id).unwrap().owner = recipient;
+
+        Ok(())
+    }
+
+    fn burn(&mut self, sender: &HumanAddr, token_id: &str) -> StdResult<()> {
+        self.require_owner_or_approved(sender, token_id)?;
+        self.tokens.remove(token_id);
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Snip721Query {
+    NftDossier { token_id: String },
+}
+
+#[derive(Deserialize)]
+struct TransferArgs {
+    recipient: HumanAddr,
+    token_ids: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct BurnArgs {
+    token_ids: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Snip721Handle {
+    MintNft {
+        token_id: Option<String>,
+        owner: Option<HumanAddr>,
+        public_metadata: Option<Metadata>,
+        private_metadata: Option<Metadata>,
+    },
+    TransferNft { recipient: HumanAddr, token_id: String },
+    BatchTransferNft { transfers: Vec<TransferArgs> },
+    BurnNft { token_id: String },
+    BatchBurnNft { burns: Vec<BurnArgs> },
+}
+
+/// Subset of the SNIP-721 dossier the bridge reads
+#[derive(Serialize)]
+struct NftDossier {
+    owner: Option<HumanAddr>,
+    public_metadata: Option<Metadata>,
+    private_metadata: Option<Metadata>,
+    display_private_metadata_error: Option<String>,
+    owner_is_public: bool,
+    private_metadata_is_public: bool,
+}
+
+#[derive(Serialize)]
+struct NftDossierResponse {
+    nft_dossier: NftDossier,
+}
+
+/// In-process SNIP-721 collections for end to end tests.
+/// Answers `nft_dossier` queries and applies the messages a bridge response emits,
+/// anything else is delegated to `MockQuerier`
+pub struct Snip721Querier {
+    pub base: MockQuerier,
+    pub collections: BTreeMap<HumanAddr, MockCollection>,
+}
+
+impl Snip721Querier {
+    /// Register an empty collection the given addresses may mint on
+    pub fn add_collection(&mut self, contract: &str, minters: &[&str]) {
+        self.collections.insert(HumanAddr(contract.into()), MockCollection {
+            minters: minters.iter().map(|m| HumanAddr((*m).into())).collect(),
+            ..Default::default()
+        });
+    }
+
+    /// Mint `token_id` to `owner` directly, bypassing minter checks
+    pub fn give(&mut self, contract: &str, token_id: &str, owner: &str, token_uri: Option<&str>) {
+        self.collection_mut(contract).tokens.insert(token_id.into(), MockToken {
+            owner: HumanAddr(owner.into()),
+            public_metadata: Some(Metadata {
+                token_uri: token_uri.map(String::from),
+                extension: None,
+            }),
+            private_metadata: None,
+        });
+    }
+
+    pub fn approve_all(&mut self, contract: &str, owner: &str, operator: &str) {
+        self.collection_mut(contract)
+            .approvals
+            .push((HumanAddr(owner.into()), HumanAddr(operator.into())));
+    }
+
+    pub fn owner_of(&self, contract: &str, token_id: &str) -> Option<HumanAddr> {
+        self.collections
+            .get(&HumanAddr(contract.into()))
+            .and_then(|c| c.tokens.get(token_id))
+            .map(|t| t.owner.clone())
+    }
+
+    pub fn token(&self, contract: &str, token_id: &str) -> Option<&MockToken> {
+        self.collections
+            .get(&HumanAddr(contract.into()))
+            .and_then(|c| c.tokens.get(token_id))
+    }
+
+    fn collection_mut(&mut self, contract: &str) -> &mut MockCollection {
+        self.collections
+            .get_mut(&HumanAddr(contract.into()))
+            .unwrap_or_else(|| panic!("collection {} isn't registered", contract))
+    }
+
+    /// Apply a message sent by `sender`, only wasm executes on known collections are supported
+    pub fn execute(&mut self, sender: &HumanAddr, msg: &CosmosMsg) -> StdResult<()> {
+        let (contract_addr, msg) = match msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, msg, .. }) => (contract_addr, msg),
+            msg => return Err(StdError::generic_err(format!("unsupported message {:?}", msg))),
+        };
+        let collection = self.collections
+            .get_mut(contract_addr)
+            .ok_or_else(|| StdError::not_found(contract_addr.0.clone()))?;
+
+        match from_slice(msg.as_slice())? {
+            Snip721Handle::MintNft { token_id, owner, public_metadata, private_metadata } => {
+                if !collection.minters.contains(sender) {
+                    return Err(StdError::unauthorized());
+                }
+                let token_id = token_id.ok_or_else(|| StdError::generic_err("token id required"))?;
+                if collection.tokens.contains_key(&token_id) {
+                    return Err(StdError::generic_err(format!("token {} already exists", token_id)));
+                }
+
+                collection.tokens.insert(token_id, MockToken {
+                    owner: owner.unwrap_or_else(|| sender.clone()),
+                    public_metadata,
+                    private_metadata,
+                });
+            }
+            Snip721Handle::TransferNft { recipient, token_id } => {
+                collection.transfer(sender, recipient, &token_id)?;
+            }
+            Snip721Handle::BatchTransferNft { transfers } => {
+                for t in transfers {
+                    for token_id in t.token_ids.iter() {
+                        collection.transfer(sender, t.recipient.clone(), token_id)?;
+                    }
+                }
+            }
+            Snip721Handle::BurnNft { token_id } => {
+                collection.burn(sender, &token_id)?;
+            }
+            Snip721Handle::BatchBurnNft { burns } => {
+                for b in burns {
+                    for token_id in b.token_ids.iter() {
+                        collection.burn(sender, token_id)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn query_collection(&self, contract: &HumanAddr, msg: &[u8]) -> QuerierResult {
+        let collection = match self.collections.get(contract) {
+            Some(c) => c,
+            None => return Err(SystemError::NoSuchContract { addr: contract.clone() }),
+        };
+
+        let res = from_slice(msg).and_then(|query| match query {
+            Snip721Query::NftDossier { token_id } => {
+                let token = collection.tokens
+                    .get(&token_id)
+                    .ok_or_else(|| StdError::generic_err(format!("token {} not found", token_id)))?;
+
+                to_binary(&NftDossierResponse {
+                    nft_dossier: NftDossier {
+                        owner: Some(token.owner.clone()),
+                        public_metadata: token.public_metadata.clone(),
+                        private_metadata: token.private_metadata.clone(),
+                        display_private_metadata_error: None,
+                        owner_is_public: true,
+                        private_metadata_is_public: false,
+                    },
+                })
+            }
+        });
+
+        Ok(res)
+    }
+}
+
+impl Querier for Snip721Querier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        match from_slice::<QueryRequest<Empty>>(bin_request) {
+            Ok(QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg, .. })) => {
+                self.query_collection(&contract_addr, msg.as_slice())
+            }
+            _ => self.base.raw_query(bin_request),
+        }
+    }
+}
+
+pub type Snip721Deps = Extern<MockStorage, MockApi, Snip721Querier>;
+
+pub fn snip721_dependencies() -> Snip721Deps {
+    Extern {
+        storage: MockStorage::default(),
+        api: MockApi::new(20),
+        querier: Snip721Querier {
+            base: MockQuerier::new(&[]),
+            collections: BTreeMap::new(),
+        },
+    }
+}
+
+/// Handle `msg` on the bridge and execute the emitted messages as the bridge contract
+pub fn handle_and_execute(deps: &mut Snip721Deps, env: Env, msg: HandleMsg) -> ContractResult<HandleResponse> {
+    let bridge = env.contract.address.clone();
+    let res = contract::handle(deps, env, msg)?;
+    for msg in res.messages.iter() {
+        deps.querier.execute(&bridge, msg)?;
+    }
+
+    Ok(res)
+}
@@ -1,15 +1,31 @@
 use schemars::{JsonSchema, schema::{SchemaObject, InstanceType, ArrayValidation}};
+use secret_toolkit::permit::Permit;
 use secret_toolkit::snip721::ViewerInfo;
 use serde::{Deserialize, Serialize};
 use borsh::{BorshSerialize, BorshDeserialize};
-use cosmwasm_std::{CanonicalAddr, HumanAddr, StdResult, Env};
+use cosmwasm_std::{Binary, CanonicalAddr, HumanAddr, StdError, StdResult, Env, Uint128};
 use serde_big_array::BigArray;
+use sha2::{Digest, Sha256};
+
+use crate::state::{ChainInfo, OutboundAction};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InitMsg {
     pub group_key: [u8; 32],
     pub chain_nonce: u64,
-    pub whitelist: Vec<CanonicalAddr>
+    pub whitelist: Vec<CanonicalAddr>,
+    #[serde(default)]
+    pub guardians: Vec<HumanAddr>,
+    /// Blocks signed rescues wait before `ExecuteRescue` may carry them out, 0 rescues right away.
+    /// Only set at init so a compromised group key can't shorten it
+    #[serde(default)]
+    pub rescue_delay: u64,
+    /// x25519 public key user transfer events are encrypted to, `None` emits them in the clear
+    #[serde(default)]
+    pub event_key: Option<[u8; 32]>,
+    /// Secret randomness for event encryption keys and viewing keys, must not be empty.
+    /// Required even without `event_key` since encryption can be enabled later with `SetEventKey`
+    pub entropy: Binary
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -59,26 +75,151 @@ pub struct SetGroupKey(pub [u8; 32]);
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct WhitelistNft(pub Vec<u8>);
 
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UnwhitelistNft(pub Vec<u8>);
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WhitelistNftBatch {
+    pub add: Vec<Vec<u8>>,
+    pub remove: Vec<Vec<u8>>
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SetGuardians(pub Vec<String>);
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressFormat {
+    EvmHex,
+    Bech32 { hrp: String },
+    Elrond,
+    Tezos,
+    Solana
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RegisterChain {
+    pub chain_nonce: u64,
+    pub name: String,
+    pub address_format: AddressFormat
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UnregisterChain(pub u64);
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Inbound,
+    Outbound
+}
+
+/// At most `max_tokens` may cross the bridge in any `window_blocks` long window.
+/// The window is tracked in 12 slots of whole blocks, so it's rounded up to a multiple of 12 blocks
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RateLimit {
+    pub max_tokens: u64,
+    pub window_blocks: u64
+}
+
+/// Set or clear (`limit: None`) a limit.
+/// `collection: None` targets the global limit shared by every collection
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SetRateLimit {
+    pub direction: Direction,
+    pub collection: Option<String>,
+    pub limit: Option<RateLimit>
+}
+
+/// Move an NFT held by the bridge to `to`.
+/// Tokens in custody for a foreign chain are only moved with `force`
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RescueNft {
+    pub contract: String,
+    pub contract_hash: String,
+    pub token_id: String,
+    pub to: String,
+    pub force: bool
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RescueSnip20 {
+    pub contract: String,
+    pub contract_hash: String,
+    pub amount: u128,
+    pub to: String
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RescueNative {
+    pub denom: String,
+    pub amount: u128,
+    pub to: String
+}
+
+/// What a signed rescue moves, kept while it waits out the rescue delay
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RescueAsset {
+    Nft(RescueNft),
+    Snip20(RescueSnip20),
+    Native(RescueNative)
+}
+
+/// Rotate the validator encryption key, `None` disables event encryption
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SetEventKey(pub Option<[u8; 32]>);
+
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct MintArgs {
     pub minter: String,
     pub minter_hash: String,
     pub token_uri: String,
+    /// Collection the token was frozen in on the source chain
+    pub contract: String,
+    /// Id of the token on the source chain, it's minted under `ForeignToken::wrapped_id`
     pub token_id: String
 }
 
+/// A token of another chain, the identity its wrapped counterpart is derived from
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ForeignToken {
+    pub chain_nonce: u64,
+    pub contract: String,
+    pub token_id: String
+}
+
+impl ForeignToken {
+    /// Id the wrapped token is minted under, hex encoded SHA-256 of the borsh encoding
+    pub fn wrapped_id(&self) -> StdResult<String> {
+        let bytes = self.try_to_vec().map_err(|e| StdError::serialize_err("borsh", e.to_string()))?;
+
+        Ok(Sha256::digest(&bytes).iter().map(|b| format!("{:02x}", b)).collect())
+    }
+}
+
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ValidateTransferNft {
     pub mint_args: MintArgs,
     pub to: String,
+    pub source_chain_nonce: u64
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ValidateTransferNftBatch {
     pub mint_args: Vec<MintArgs>,
-    pub to: String
+    pub to: String,
+    pub source_chain_nonce: u64
 }
 
+/// `ValidateTransferNft` for its recipient to submit.
+/// Signed under its own context, so neither signature can be used in place of the other
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimTransfer(pub ValidateTransferNft);
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimTransferBatch(pub ValidateTransferNftBatch);
+
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct UnfreezeArgs {
     pub contract: String,
@@ -89,13 +230,31 @@ pub struct UnfreezeArgs {
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ValidateUnfreezeNft {
     pub unfreeze_args: UnfreezeArgs,
-    pub to: String
+    pub to: String,
+    pub source_chain_nonce: u64
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ValidateUnfreezeNftBatch {
     pub unfreeze_args: Vec<UnfreezeArgs>,
-    pub to: String
+    pub to: String,
+    pub source_chain_nonce: u64
+}
+
+/// Give back what outbound freeze `action_id` took, after it failed on the destination.
+/// Frozen tokens and optionally the fee go to the sender recorded at freeze time,
+/// refused if any of them was unfrozen since, even when it was frozen again
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RefundFreeze {
+    pub action_id: u128,
+    pub refund_fee: bool
+}
+
+/// Mint the wrapped tokens burned by outbound withdraw `action_id` again, to their owner at burn time
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RefundWithdraw {
+    pub action_id: u128,
+    pub refund_fee: bool
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -105,10 +264,31 @@ pub enum HandleMsg {
     WithdrawFees { info: ValidatorInfo, inner: WithdrawFees },
     SetGroupKey { info: ValidatorInfo, inner: SetGroupKey },
     WhitelistNft { info: ValidatorInfo, inner: WhitelistNft },
+    UnwhitelistNft { info: ValidatorInfo, inner: UnwhitelistNft },
+    WhitelistNftBatch { info: ValidatorInfo, inner: WhitelistNftBatch },
+    SetGuardians { info: ValidatorInfo, inner: SetGuardians },
+    EmergencyPause {},
+    RegisterChain { info: ValidatorInfo, inner: RegisterChain },
+    UnregisterChain { info: ValidatorInfo, inner: UnregisterChain },
+    SetRateLimit { info: ValidatorInfo, inner: SetRateLimit },
+    RescueNft { info: ValidatorInfo, inner: RescueNft },
+    RescueSnip20 { info: ValidatorInfo, inner: RescueSnip20 },
+    RescueNative { info: ValidatorInfo, inner: RescueNative },
+    /// Carry out a queued rescue once its delay has passed, anyone may send it
+    ExecuteRescue { action_id: u128 },
+    SetEventKey { info: ValidatorInfo, inner: SetEventKey },
     ValidateTransferNft { info: ValidatorInfo, inner: ValidateTransferNft },
     ValidateTransferNftBatch { info: ValidatorInfo, inner: ValidateTransferNftBatch },
     ValidateUnfreezeNft { info: ValidatorInfo, inner: ValidateUnfreezeNft },
     ValidateUnfreezeNftBatch { info: ValidatorInfo, inner: ValidateUnfreezeNftBatch  },
+    RefundFreeze { info: ValidatorInfo, inner: RefundFreeze },
+    RefundWithdraw { info: ValidatorInfo, inner: RefundWithdraw },
+    /// Transfer completed by its recipient, consuming the same action id a relayer push would
+    ClaimTransfer { info: ValidatorInfo, inner: ClaimTransfer },
+    ClaimTransferBatch { info: ValidatorInfo, inner: ClaimTransferBatch },
+    SetViewingKey { key: String, padding: Option<String> },
+    CreateViewingKey { entropy: String, padding: Option<String> },
+    RevokePermit { permit_name: String, padding: Option<String> },
     FreezeNft { contract: HumanAddr, contract_hash: String, token_id: String, viewer: Option<ViewerInfo>, to: String, chain_nonce: u64, minter: String },
     FreezeNftBatch { contract: HumanAddr, contract_hash: String, token_ids: Vec<String>, viewer: Option<ViewerInfo>, to: String, chain_nonce: u64, minter: String },
     WithdrawNft { burner: HumanAddr, burner_hash: String, token_id: String, viewer: Option<ViewerInfo>, to: String, chain_nonce: u64 },
@@ -125,23 +305,345 @@ pub struct BridgeAction<T: BorshSerialize> {
 
 impl<T: BorshSerialize> BridgeAction<T> {
     pub fn new(env: &Env, action_id: u128, chain_nonce: u64, inner: T) -> StdResult<Self> {
-        Ok(BridgeAction {
+        Ok(Self::for_contract(env.contract.address.0.clone(), action_id, chain_nonce, inner))
+    }
+
+    pub fn for_contract(sc_addr: String, action_id: u128, chain_nonce: u64, inner: T) -> Self {
+        BridgeAction {
             chain_nonce,
             action_id,
             inner,
-            sc_addr: env.contract.address.0.clone()
-        })
+            sc_addr
+        }
     }
 }
 
+macro_rules! signable {
+    (config: [$($cfg:ident),*], action: [$($act:ident),*]) => {
+        /// Inner payload of a validator signed `HandleMsg`, without the signature
+        #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+        #[serde(rename_all = "snake_case")]
+        pub enum SignableMsg {
+            $($cfg($cfg),)*
+            $($act($act),)*
+        }
+
+        impl SignableMsg {
+            /// Domain separation prefix hashed ahead of the action
+            pub fn context(&self) -> &'static [u8] {
+                match self {
+                    $(SignableMsg::$cfg(_) => stringify!($cfg).as_bytes(),)*
+                    $(SignableMsg::$act(_) => stringify!($act).as_bytes(),)*
+                }
+            }
+
+            /// Whether the action id is consumed from the config action space
+            pub fn is_config(&self) -> bool {
+                match self {
+                    $(SignableMsg::$cfg(_) => true,)*
+                    $(SignableMsg::$act(_) => false,)*
+                }
+            }
+
+            /// Attach the validator signature, giving the message to submit
+            pub fn into_handle_msg(self, info: ValidatorInfo) -> HandleMsg {
+                match self {
+                    $(SignableMsg::$cfg(inner) => HandleMsg::$cfg { info, inner },)*
+                    $(SignableMsg::$act(inner) => HandleMsg::$act { info, inner },)*
+                }
+            }
+
+            /// Borsh encoded `BridgeAction` the group key signs over
+            pub fn action_bytes(&self, sc_addr: &HumanAddr, action_id: u128, chain_nonce: u64) -> StdResult<Vec<u8>> {
+                match self {
+                    $(SignableMsg::$cfg(inner) => crate::signing::action_bytes(
+                        &BridgeAction::for_contract(sc_addr.0.clone(), action_id, chain_nonce, inner.clone())
+                    ),)*
+                    $(SignableMsg::$act(inner) => crate::signing::action_bytes(
+                        &BridgeAction::for_contract(sc_addr.0.clone(), action_id, chain_nonce, inner.clone())
+                    ),)*
+                }
+            }
+        }
+
+        impl HandleMsg {
+            /// Split a validator signed message into its signature and payload
+            pub fn into_signed(self) -> Option<(ValidatorInfo, SignableMsg)> {
+                match self {
+                    $(HandleMsg::$cfg { info, inner } => Some((info, SignableMsg::$cfg(inner))),)*
+                    $(HandleMsg::$act { info, inner } => Some((info, SignableMsg::$act(inner))),)*
+                    _ => None
+                }
+            }
+        }
+    };
+}
+
+signable!(
+    config: [
+        SetPause, WithdrawFees, SetGroupKey, WhitelistNft, UnwhitelistNft, WhitelistNftBatch,
+        SetGuardians, RegisterChain, UnregisterChain, SetRateLimit, RescueNft, RescueSnip20,
+        RescueNative, SetEventKey
+    ],
+    action: [
+        ValidateTransferNft, ValidateTransferNftBatch, ValidateUnfreezeNft, ValidateUnfreezeNftBatch,
+        RefundFreeze, RefundWithdraw, ClaimTransfer, ClaimTransferBatch
+    ]
+);
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
+    GetConfig,
     GetGroupKey,
     GetPaused,
     GetChainNonce,
     GetEventCnt,
+    GetGuardians,
     GetWhitelisted { addr: CanonicalAddr },
+    ListWhitelisted { start: Option<u32>, limit: Option<u32> },
+    GetChain { chain_nonce: u64 },
+    ListChains,
+    GetRateLimit { direction: Direction, collection: Option<String>, height: u64 },
+    GetInCustody { contract: HumanAddr, token_id: String },
+    GetReceipt { source_chain_nonce: u64, action_id: u128 },
+    /// Bridge history of `address`, authenticated with its viewing key
+    GetHistory { address: HumanAddr, key: String, start: Option<u32>, limit: Option<u32> },
+    WithPermit { permit: Permit, query: QueryWithPermit },
+    /// Payload and digest the group key has to sign for `msg` under `action_id`
+    GetSigningPayload { msg: SignableMsg, action_id: u128 },
+    /// Dry run of the signature check `msg` would go through when handled
+    VerifySignature { msg: HandleMsg },
+    /// What outbound action `action_id` took from its sender and whether it was refunded
+    GetOutbound { action_id: u128 },
+    /// Wrapped id of a foreign token and the collection it was minted in, if it was
+    GetWrappedToken { source_chain_nonce: u64, contract: String, token_id: String },
+    /// Foreign token the wrapped `token_id` of `contract` stands for
+    GetForeignToken { contract: HumanAddr, token_id: String },
     GetActionConsumed { action: u128 },
-    GetActionConfigConsumed { action: u128 }
+    GetActionConfigConsumed { action: u128 },
+    /// Consumed status of each of `actions`, at most 100 ids per query
+    GetActionsConsumed { space: ActionSpace, actions: Vec<u128> },
+    /// Consumed ids in `[start, end)`, scanning at most 1000 ids per page
+    ListActionsConsumed { space: ActionSpace, start: u128, end: Option<u128>, limit: Option<u32> }
+}
+
+/// Replay protection namespace an action id is consumed from
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionSpace {
+    /// `Validate*` messages
+    Validate,
+    /// Configuration and rescue messages
+    Config
+}
+
+/// Queries authenticated by a permit signed by the queried address
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryWithPermit {
+    GetHistory { start: Option<u32>, limit: Option<u32> }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RateLimitResponse {
+    pub limit: Option<RateLimit>,
+    pub remaining: Option<u64>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CompletionKind {
+    Transfer,
+    Unfreeze
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReceiptToken {
+    pub contract: HumanAddr,
+    pub token_id: String
+}
+
+/// Proof that a foreign action was executed on this chain.
+/// Stored per `(source_chain_nonce, action_id)` and returned as the handle `data`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CompletionReceipt {
+    pub kind: CompletionKind,
+    pub source_chain_nonce: u64,
+    pub action_id: u128,
+    pub to: HumanAddr,
+    pub tokens: Vec<ReceiptToken>,
+    pub block_height: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ViewingKeyResponse {
+    pub key: String
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryKind {
+    Freeze,
+    Withdraw,
+    ReceiveTransfer,
+    ReceiveUnfreeze
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryStatus {
+    /// Sent from this chain. Delivery happens on the destination chain and isn't reported back,
+    /// so outbound entries stay pending unless they are refunded
+    Pending,
+    Completed,
+    Refunded
+}
+
+/// A bridge action involving a user.
+/// `chain_nonce` and `counterparty` refer to the foreign side of the action,
+/// the counterparty is only known for actions sent from this chain
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HistoryEntry {
+    pub action_id: u128,
+    pub kind: HistoryKind,
+    pub status: HistoryStatus,
+    pub chain_nonce: u64,
+    pub counterparty: Option<String>,
+    pub tokens: Vec<ReceiptToken>,
+    pub block_height: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HistoryResponse {
+    pub entries: Vec<HistoryEntry>,
+    pub total: u32
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub version: String,
+    pub paused: bool,
+    pub group_key: [u8; 32],
+    pub chain_nonce: u64,
+    pub event_cnt: Uint128,
+    pub guardians: Vec<HumanAddr>,
+    pub event_key: Option<[u8; 32]>,
+    /// Denom outbound transfer fees are paid in
+    pub fee_denom: String,
+    /// Fees collected so far, paid out by `WithdrawFees`
+    pub fee_balance: Uint128,
+    /// Blocks signed rescues wait before `ExecuteRescue` may carry them out
+    pub rescue_delay: u64,
+    pub chains: Vec<ChainInfo>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PausedResponse {
+    pub paused: bool
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GroupKeyResponse {
+    pub group_key: [u8; 32]
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ChainNonceResponse {
+    pub chain_nonce: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EventCntResponse {
+    pub event_cnt: Uint128
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GuardiansResponse {
+    pub guardians: Vec<HumanAddr>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WhitelistedResponse {
+    pub whitelisted: bool
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WhitelistResponse {
+    pub contracts: Vec<HumanAddr>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ChainResponse {
+    pub chain: Option<ChainInfo>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ChainsResponse {
+    pub chains: Vec<ChainInfo>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReceiptResponse {
+    pub receipt: Option<CompletionReceipt>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InCustodyResponse {
+    pub in_custody: bool
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OutboundResponse {
+    pub outbound: Option<OutboundAction>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WrappedTokenResponse {
+    pub token_id: String,
+    pub contract: Option<HumanAddr>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ForeignTokenResponse {
+    pub foreign: Option<ForeignToken>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ActionConsumedResponse {
+    pub consumed: bool
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SigningPayloadResponse {
+    pub context: Binary,
+    /// Borsh encoded `BridgeAction`
+    pub action: Binary,
+    /// SHA-512 of `context || action`, the message passed to ed25519
+    pub digest: Binary
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VerifySignatureResponse {
+    pub valid: bool,
+    pub reason: Option<String>,
+    pub digest: Binary
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ActionStatus {
+    pub action: u128,
+    pub consumed: bool
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ActionsConsumedResponse {
+    pub actions: Vec<ActionStatus>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConsumedActionsResponse {
+    pub actions: Vec<u128>,
+    /// Where to resume scanning, `None` once the range is exhausted
+    pub next: Option<u128>
 }
@@ -0,0 +1,81 @@
+use cosmwasm_std::{CanonicalAddr, StdResult, Storage};
+
+use crate::error::{ContractError, ContractResult};
+use crate::msg::{Direction, RateLimit};
+use crate::state::{rate_limit_read, rate_usage, rate_usage_read, RateSlot, RateUsage};
+
+/// Number of slots a window is split into.
+/// Usage is tracked per slot so the window rolls forward one slot at a time
+const WINDOW_SLOTS: u64 = 12;
+
+/// Blocks per slot, rounded up so the tracked window may be longer than `window_blocks`.
+/// Windows shorter than `WINDOW_SLOTS` blocks are `WINDOW_SLOTS` blocks long
+fn slot_size(limit: &RateLimit) -> u64 {
+    // Rounded up without `+ WINDOW_SLOTS - 1` which overflows for huge windows
+    (limit.window_blocks / WINDOW_SLOTS + (limit.window_blocks % WINDOW_SLOTS != 0) as u64).max(1)
+}
+
+fn used(usage: &RateUsage, current: u64) -> u64 {
+    usage.slots
+        .iter()
+        .filter(|s| s.slot + WINDOW_SLOTS > current)
+        .map(|s| s.count)
+        .sum()
+}
+
+/// Tokens that may still cross at `height`, `None` if no limit is set
+pub fn remaining<S: Storage>(
+    storage: &S,
+    direction: &Direction,
+    collection: Option<&CanonicalAddr>,
+    height: u64
+) -> StdResult<Option<u64>> {
+    let limit = match rate_limit_read(storage, direction, collection).may_load()? {
+        Some(limit) => limit,
+        None => return Ok(None)
+    };
+    let usage = rate_usage_read(storage, direction, collection).may_load()?.unwrap_or_default();
+
+    Ok(Some(limit.max_tokens.saturating_sub(used(&usage, height / slot_size(&limit)))))
+}
+
+fn consume_limit<S: Storage>(
+    storage: &mut S,
+    direction: &Direction,
+    collection: Option<&CanonicalAddr>,
+    count: u64,
+    height: u64
+) -> ContractResult<()> {
+    let limit = match rate_limit_read(storage, direction, collection).may_load()? {
+        Some(limit) => limit,
+        None => return Ok(())
+    };
+    let mut usage = rate_usage_read(storage, direction, collection).may_load()?.unwrap_or_default();
+
+    let current = height / slot_size(&limit);
+    usage.slots.retain(|s| s.slot + WINDOW_SLOTS > current);
+    if used(&usage, current).saturating_add(count) > limit.max_tokens {
+        return Err(ContractError::RateLimitExceeded {});
+    }
+
+    match usage.slots.iter_mut().find(|s| s.slot == current) {
+        Some(s) => s.count += count,
+        None => usage.slots.push(RateSlot { slot: current, count })
+    }
+
+    rate_usage(storage, direction, collection).save(&usage)?;
+
+    Ok(())
+}
+
+/// Account `count` tokens of `collection` against both the global and the collection limit
+pub fn consume<S: Storage>(
+    storage: &mut S,
+    direction: Direction,
+    collection: &CanonicalAddr,
+    count: u64,
+    height: u64
+) -> ContractResult<()> {
+    consume_limit(storage, &direction, None, count, height)?;
+    consume_limit(storage, &direction, Some(collection), count, height)
+}
@@ -0,0 +1,121 @@
+use std::iter::repeat;
+
+use cosmwasm_std::{HumanAddr, StdError, StdResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::decode::{BridgeEvent, DecodedLog};
+use crate::events::TransferInfo;
+use crate::msg::{
+    ForeignToken, MintArgs, SignableMsg, UnfreezeArgs, ValidateTransferNft, ValidateTransferNftBatch, ValidateUnfreezeNft,
+    ValidateUnfreezeNftBatch,
+};
+
+/// An outbound action as identified by the peer bridge that logged it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PeerAction {
+    pub chain_nonce: u64,
+    pub action_id: u128,
+}
+
+/// What the destination needs beyond the events of the source bridge
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Route {
+    /// Code hash of the destination collection, the minter for transfers or the original collection for unfreezes
+    pub code_hash: String,
+    /// Original collection on the destination, for withdraw events of tokens without a foreign identity
+    pub contract: Option<String>,
+    /// Wrapped collection on the source whose withdraws unfreeze `contract`, withdraws from any other are refused
+    pub burner: Option<String>,
+}
+
+fn mint_args(info: TransferInfo, contract: &HumanAddr, minter: &str, route: &Route) -> StdResult<MintArgs> {
+    let token_uri = info.public_metadata
+        .and_then(|m| m.token_uri)
+        .ok_or_else(|| StdError::generic_err(format!("token {} has no public token uri", info.token_id)))?;
+
+    Ok(MintArgs {
+        minter: minter.to_string(),
+        minter_hash: route.code_hash.clone(),
+        token_uri,
+        contract: contract.0.clone(),
+        token_id: info.token_id,
+    })
+}
+
+fn unfreeze_args(token_id: String, foreign: Option<ForeignToken>, burner: &HumanAddr, route: &Route) -> StdResult<UnfreezeArgs> {
+    let (contract, token_id) = match foreign {
+        Some(foreign) => (foreign.contract, foreign.token_id),
+        None => {
+            let contract = route.contract
+                .clone()
+                .ok_or_else(|| StdError::generic_err("unfreezing requires the original collection"))?;
+            // otherwise any collection could burn a token to unfreeze its namesake in `contract`
+            if route.burner.as_deref() != Some(burner.as_str()) {
+                return Err(StdError::generic_err(format!("{} isn't the wrapped collection of {}", burner, contract)));
+            }
+            (contract, token_id)
+        }
+    };
+
+    Ok(UnfreezeArgs {
+        contract,
+        contract_hash: route.code_hash.clone(),
+        token_id,
+    })
+}
+
+/// Validator signed message completing an outbound action of a peer bridge on this one.
+/// Returns it along with the peer action, for validators to track what they relayed.
+/// The message must be signed under an action id validators allocate on this bridge:
+/// ids of different peers collide, and with the refunds of this bridge
+pub fn relayed_action(log: &DecodedLog, route: &Route) -> StdResult<(PeerAction, SignableMsg)> {
+    let info = log.events
+        .iter()
+        .find_map(|ev| match ev {
+            BridgeEvent::BridgeEventInfo(info) => Some(info.clone()),
+            _ => None,
+        })
+        .ok_or_else(|| StdError::not_found("bridge_event_info"))?;
+    let to = info.to;
+    let source_chain_nonce = info.source_chain_nonce;
+
+    let msg = log.events
+        .iter()
+        .find_map(|ev| match ev.clone() {
+            BridgeEvent::TransferSnip721(ev) => Some(
+                mint_args(ev.info, &ev.contract_addr, &ev.mint_with, route).map(|mint_args| {
+                    SignableMsg::ValidateTransferNft(ValidateTransferNft { mint_args, to: to.clone(), source_chain_nonce })
+                })
+            ),
+            BridgeEvent::TransferSnip721Batch(ev) => Some(
+                ev.infos
+                    .into_iter()
+                    .map(|info| mint_args(info, &ev.contract_addr, &ev.mint_with, route))
+                    .collect::<StdResult<Vec<_>>>()
+                    .map(|mint_args| {
+                        SignableMsg::ValidateTransferNftBatch(ValidateTransferNftBatch { mint_args, to: to.clone(), source_chain_nonce })
+                    })
+            ),
+            BridgeEvent::UnfreezeSnip721(ev) => Some(
+                unfreeze_args(ev.token_id, ev.foreign, &ev.burner, route).map(|unfreeze_args| {
+                    SignableMsg::ValidateUnfreezeNft(ValidateUnfreezeNft { unfreeze_args, to: to.clone(), source_chain_nonce })
+                })
+            ),
+            BridgeEvent::UnfreezeSnip721Batch(ev) => Some(
+                ev.token_ids
+                    .into_iter()
+                    // events of older versions carry no foreign identities
+                    .zip(ev.foreign.into_iter().chain(repeat(None)))
+                    .map(|(token_id, foreign)| unfreeze_args(token_id, foreign, &ev.burner, route))
+                    .collect::<StdResult<Vec<_>>>()
+                    .map(|unfreeze_args| {
+                        SignableMsg::ValidateUnfreezeNftBatch(ValidateUnfreezeNftBatch { unfreeze_args, to: to.clone(), source_chain_nonce })
+                    })
+            ),
+            _ => None,
+        })
+        .ok_or_else(|| StdError::generic_err("log has no outbound transfer or withdraw event"))??;
+
+    Ok((PeerAction { chain_nonce: source_chain_nonce, action_id: info.action_id }, msg))
+}
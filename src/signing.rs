@@ -0,0 +1,29 @@
+use borsh::BorshSerialize;
+use cosmwasm_std::{StdError, StdResult};
+use ed25519_compact::{PublicKey, Signature};
+use sha2::{Digest, Sha512};
+
+use crate::msg::{BridgeAction, Sig};
+
+/// Borsh encoding of a `BridgeAction`, the part of the signed payload following the context
+pub fn action_bytes<T: BorshSerialize>(action: &BridgeAction<T>) -> StdResult<Vec<u8>> {
+    action.try_to_vec().map_err(|e|
+        StdError::serialize_err("borsh", e.to_string())
+    )
+}
+
+/// SHA-512 of `context || action`, this digest is what the group key signs
+pub fn signing_digest(context: &[u8], action: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha512::new();
+    hasher.update(context);
+    hasher.update(action);
+
+    hasher.finalize().to_vec()
+}
+
+pub fn verify_digest(group_key: &[u8; 32], digest: &[u8], sig: &Sig) -> bool {
+    let sig = Signature::new(sig.0);
+    let key = PublicKey::new(*group_key);
+
+    key.verify(digest, &sig).is_ok()
+}
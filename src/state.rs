@@ -1,17 +1,93 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Storage, CanonicalAddr, Uint128};
+use cosmwasm_std::{Storage, CanonicalAddr, HumanAddr, Uint128, StdResult};
 use cosmwasm_storage::{singleton, singleton_read, ReadonlySingleton, Singleton};
 
+use crate::msg::{ActionSpace, AddressFormat, CompletionReceipt, Direction, ForeignToken, HistoryEntry, HistoryKind, RateLimit, RescueAsset};
+
 pub static CONFIG_KEY: &[u8] = b"config";
+pub static WHITELIST_LEN_KEY: &[u8] = b"whitelist_len";
+pub static WHITELIST_IDX_KEY: &[u8] = b"whitelist_idx";
+pub static WHITELIST_POS_KEY: &[u8] = b"whitelist_pos";
+pub static CHAIN_KEY: &[u8] = b"chain";
+pub static CHAINS_KEY: &[u8] = b"chains";
+pub static RATE_LIMIT_KEY: &[u8] = b"rate_limit";
+pub static RATE_USAGE_KEY: &[u8] = b"rate_usage";
+pub static CUSTODY_KEY: &[u8] = b"custody";
+pub static PENDING_RESCUE_KEY: &[u8] = b"pending_rescue";
+pub static PRNG_SEED_KEY: &[u8] = b"prng_seed";
+pub static ENCRYPTED_CNT_KEY: &[u8] = b"encrypted_cnt";
+pub static RECEIPT_KEY: &[u8] = b"receipt";
+pub static CONTRACT_ADDR_KEY: &[u8] = b"contract_addr";
+pub static VIEWING_KEY_KEY: &[u8] = b"viewing_key";
+pub static HISTORY_KEY: &[u8] = b"history";
+pub static HISTORY_LEN_KEY: &[u8] = b"history_len";
+pub static HISTORY_REF_KEY: &[u8] = b"history_ref";
+pub static OUTBOUND_KEY: &[u8] = b"outbound";
+pub static WRAPPED_MINTER_KEY: &[u8] = b"wrapped_minter";
+pub static FOREIGN_TOKEN_KEY: &[u8] = b"foreign_token";
+pub const PREFIX_REVOKED_PERMITS: &str = "revoked_permits";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
     pub group_key: [u8; 32],
     pub event_cnt: Uint128,
     pub paused: bool,
-    pub chain_nonce: u64
+    pub chain_nonce: u64,
+    pub guardians: Vec<CanonicalAddr>,
+    pub rescue_delay: u64,
+    pub event_key: Option<[u8; 32]>
+}
+
+/// A destination chain the bridge may send to
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ChainInfo {
+    pub chain_nonce: u64,
+    pub name: String,
+    pub address_format: AddressFormat
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RateSlot {
+    pub slot: u64,
+    pub count: u64
+}
+
+/// Tokens moved per slot of a rate limit window
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct RateUsage {
+    pub slots: Vec<RateSlot>
+}
+
+/// A signed rescue waiting for `ExecuteRescue`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingRescue {
+    /// First block it may be executed in
+    pub ready_at: u64,
+    pub asset: RescueAsset
+}
+
+/// Location of the history entry of an outbound action
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HistoryRef {
+    pub owner: CanonicalAddr,
+    pub idx: u32
+}
+
+/// What an outbound action took from its sender, kept so it can be refunded
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OutboundAction {
+    pub kind: HistoryKind,
+    pub sender: HumanAddr,
+    /// The collection frozen from, or the wrapped collection burned from
+    pub contract: HumanAddr,
+    pub contract_hash: String,
+    pub token_ids: Vec<String>,
+    /// Public token uris of burned tokens, to mint them again on refund
+    pub token_uris: Vec<String>,
+    pub fee: u128,
+    pub refunded: bool
 }
 
 pub fn config<S: Storage>(storage: &mut S) -> Singleton<S, State> {
@@ -38,6 +114,16 @@ pub fn action_config_read<S: Storage>(storage: &S, action: u128) -> ReadonlySing
     singleton_read(storage, &[CONFIG_KEY, &action.to_be_bytes()].concat())
 }
 
+/// Whether `action` was consumed in `space`, ids never seen are not consumed
+pub fn action_consumed<S: Storage>(storage: &S, space: &ActionSpace, action: u128) -> StdResult<bool> {
+    let consumed = match space {
+        ActionSpace::Validate => action_read(storage, action).may_load()?,
+        ActionSpace::Config => action_config_read(storage, action).may_load()?
+    };
+
+    Ok(consumed.unwrap_or(false))
+}
+
 pub fn whitelisted<S: Storage>(storage: &mut S, address: CanonicalAddr) -> Singleton<S, bool> {
     singleton(storage, address.as_slice())
 }
@@ -45,3 +131,246 @@ pub fn whitelisted<S: Storage>(storage: &mut S, address: CanonicalAddr) -> Singl
 pub fn whitelisted_read<S: Storage>(storage: &S, address: CanonicalAddr) -> ReadonlySingleton<S, bool> {
     singleton_read(storage, address.as_slice())
 }
+
+fn whitelist_len<S: Storage>(storage: &mut S) -> Singleton<S, u32> {
+    singleton(storage, WHITELIST_LEN_KEY)
+}
+
+fn whitelist_len_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, u32> {
+    singleton_read(storage, WHITELIST_LEN_KEY)
+}
+
+fn whitelist_idx<S: Storage>(storage: &mut S, idx: u32) -> Singleton<S, CanonicalAddr> {
+    singleton(storage, &[WHITELIST_IDX_KEY, &idx.to_be_bytes()].concat())
+}
+
+fn whitelist_idx_read<S: Storage>(storage: &S, idx: u32) -> ReadonlySingleton<S, CanonicalAddr> {
+    singleton_read(storage, &[WHITELIST_IDX_KEY, &idx.to_be_bytes()].concat())
+}
+
+fn whitelist_pos<S: Storage>(storage: &mut S, address: &CanonicalAddr) -> Singleton<S, u32> {
+    singleton(storage, &[WHITELIST_POS_KEY, address.as_slice()].concat())
+}
+
+fn whitelist_pos_read<S: Storage>(storage: &S, address: &CanonicalAddr) -> ReadonlySingleton<S, u32> {
+    singleton_read(storage, &[WHITELIST_POS_KEY, address.as_slice()].concat())
+}
+
+/// Whitelist a collection, keeping the index used for listing in sync
+pub fn add_whitelist<S: Storage>(storage: &mut S, address: CanonicalAddr) -> StdResult<()> {
+    if whitelist_pos_read(storage, &address).may_load()?.is_some() {
+        return Ok(());
+    }
+
+    let len = whitelist_len_read(storage).may_load()?.unwrap_or(0);
+    whitelist_idx(storage, len).save(&address)?;
+    whitelist_pos(storage, &address).save(&len)?;
+    whitelist_len(storage).save(&(len + 1))?;
+
+    whitelisted(storage, address).save(&true)
+}
+
+/// Remove a collection from the whitelist.
+/// The last index entry is swapped into the freed slot so the index stays dense
+pub fn remove_whitelist<S: Storage>(storage: &mut S, address: CanonicalAddr) -> StdResult<()> {
+    whitelisted(storage, address.clone()).remove();
+
+    let pos = match whitelist_pos_read(storage, &address).may_load()? {
+        Some(pos) => pos,
+        None => return Ok(())
+    };
+    let last = whitelist_len_read(storage).load()? - 1;
+
+    if pos != last {
+        let moved = whitelist_idx_read(storage, last).load()?;
+        whitelist_idx(storage, pos).save(&moved)?;
+        whitelist_pos(storage, &moved).save(&pos)?;
+    }
+    whitelist_idx(storage, last).remove();
+    whitelist_pos(storage, &address).remove();
+    whitelist_len(storage).save(&last)
+}
+
+pub fn whitelist_page<S: Storage>(storage: &S, start: u32, limit: u32) -> StdResult<Vec<CanonicalAddr>> {
+    let len = whitelist_len_read(storage).may_load()?.unwrap_or(0);
+    let end = len.min(start.saturating_add(limit));
+
+    (start..end)
+        .map(|idx| whitelist_idx_read(storage, idx).load())
+        .collect()
+}
+
+pub fn chain<S: Storage>(storage: &mut S, chain_nonce: u64) -> Singleton<S, ChainInfo> {
+    singleton(storage, &[CHAIN_KEY, &chain_nonce.to_be_bytes()].concat())
+}
+
+pub fn chain_read<S: Storage>(storage: &S, chain_nonce: u64) -> ReadonlySingleton<S, ChainInfo> {
+    singleton_read(storage, &[CHAIN_KEY, &chain_nonce.to_be_bytes()].concat())
+}
+
+/// Nonces of every registered chain, used for listing
+pub fn chain_nonces<S: Storage>(storage: &mut S) -> Singleton<S, Vec<u64>> {
+    singleton(storage, CHAINS_KEY)
+}
+
+pub fn chain_nonces_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, Vec<u64>> {
+    singleton_read(storage, CHAINS_KEY)
+}
+
+fn rate_key(prefix: &[u8], direction: &Direction, collection: Option<&CanonicalAddr>) -> Vec<u8> {
+    let direction: &[u8] = match direction {
+        Direction::Inbound => b"in/",
+        Direction::Outbound => b"out/"
+    };
+
+    [prefix, direction, collection.map(|c| c.as_slice()).unwrap_or_default()].concat()
+}
+
+pub fn rate_limit<S: Storage>(storage: &mut S, direction: &Direction, collection: Option<&CanonicalAddr>) -> Singleton<S, RateLimit> {
+    singleton(storage, &rate_key(RATE_LIMIT_KEY, direction, collection))
+}
+
+pub fn rate_limit_read<S: Storage>(storage: &S, direction: &Direction, collection: Option<&CanonicalAddr>) -> ReadonlySingleton<S, RateLimit> {
+    singleton_read(storage, &rate_key(RATE_LIMIT_KEY, direction, collection))
+}
+
+pub fn rate_usage<S: Storage>(storage: &mut S, direction: &Direction, collection: Option<&CanonicalAddr>) -> Singleton<S, RateUsage> {
+    singleton(storage, &rate_key(RATE_USAGE_KEY, direction, collection))
+}
+
+pub fn rate_usage_read<S: Storage>(storage: &S, direction: &Direction, collection: Option<&CanonicalAddr>) -> ReadonlySingleton<S, RateUsage> {
+    singleton_read(storage, &rate_key(RATE_USAGE_KEY, direction, collection))
+}
+
+/// Action id of the freeze a token is held under, set while the bridge holds it on behalf of a foreign chain
+pub fn custody<S: Storage>(storage: &mut S, contract: &CanonicalAddr, token_id: &str) -> Singleton<S, u128> {
+    singleton(storage, &[CUSTODY_KEY, contract.as_slice(), b"/", token_id.as_bytes()].concat())
+}
+
+pub fn custody_read<S: Storage>(storage: &S, contract: &CanonicalAddr, token_id: &str) -> ReadonlySingleton<S, u128> {
+    singleton_read(storage, &[CUSTODY_KEY, contract.as_slice(), b"/", token_id.as_bytes()].concat())
+}
+
+/// Rescue queued under the config action id that signed it
+pub fn pending_rescue<S: Storage>(storage: &mut S, action_id: u128) -> Singleton<S, PendingRescue> {
+    singleton(storage, &[PENDING_RESCUE_KEY, &action_id.to_be_bytes()].concat())
+}
+
+pub fn pending_rescue_read<S: Storage>(storage: &S, action_id: u128) -> ReadonlySingleton<S, PendingRescue> {
+    singleton_read(storage, &[PENDING_RESCUE_KEY, &action_id.to_be_bytes()].concat())
+}
+
+pub fn prng_seed<S: Storage>(storage: &mut S) -> Singleton<S, [u8; 32]> {
+    singleton(storage, PRNG_SEED_KEY)
+}
+
+pub fn prng_seed_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, [u8; 32]> {
+    singleton_read(storage, PRNG_SEED_KEY)
+}
+
+/// Number of encrypted event payloads emitted so far
+pub fn encrypted_cnt<S: Storage>(storage: &mut S) -> Singleton<S, u64> {
+    singleton(storage, ENCRYPTED_CNT_KEY)
+}
+
+pub fn receipt<S: Storage>(storage: &mut S, source_chain_nonce: u64, action_id: u128) -> Singleton<S, CompletionReceipt> {
+    singleton(storage, &[RECEIPT_KEY, &source_chain_nonce.to_be_bytes(), &action_id.to_be_bytes()].concat())
+}
+
+pub fn receipt_read<S: Storage>(storage: &S, source_chain_nonce: u64, action_id: u128) -> ReadonlySingleton<S, CompletionReceipt> {
+    singleton_read(storage, &[RECEIPT_KEY, &source_chain_nonce.to_be_bytes(), &action_id.to_be_bytes()].concat())
+}
+
+/// Our own address, needed to validate permits in queries
+pub fn contract_addr<S: Storage>(storage: &mut S) -> Singleton<S, HumanAddr> {
+    singleton(storage, CONTRACT_ADDR_KEY)
+}
+
+pub fn contract_addr_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, HumanAddr> {
+    singleton_read(storage, CONTRACT_ADDR_KEY)
+}
+
+/// Hash of the owner's viewing key
+pub fn viewing_key<S: Storage>(storage: &mut S, owner: &CanonicalAddr) -> Singleton<S, [u8; 32]> {
+    singleton(storage, &[VIEWING_KEY_KEY, owner.as_slice()].concat())
+}
+
+pub fn viewing_key_read<S: Storage>(storage: &S, owner: &CanonicalAddr) -> ReadonlySingleton<S, [u8; 32]> {
+    singleton_read(storage, &[VIEWING_KEY_KEY, owner.as_slice()].concat())
+}
+
+fn history_len<S: Storage>(storage: &mut S, owner: &CanonicalAddr) -> Singleton<S, u32> {
+    singleton(storage, &[HISTORY_LEN_KEY, owner.as_slice()].concat())
+}
+
+fn history_len_read<S: Storage>(storage: &S, owner: &CanonicalAddr) -> ReadonlySingleton<S, u32> {
+    singleton_read(storage, &[HISTORY_LEN_KEY, owner.as_slice()].concat())
+}
+
+pub fn history<S: Storage>(storage: &mut S, owner: &CanonicalAddr, idx: u32) -> Singleton<S, HistoryEntry> {
+    singleton(storage, &[HISTORY_KEY, owner.as_slice(), &idx.to_be_bytes()].concat())
+}
+
+pub fn history_read<S: Storage>(storage: &S, owner: &CanonicalAddr, idx: u32) -> ReadonlySingleton<S, HistoryEntry> {
+    singleton_read(storage, &[HISTORY_KEY, owner.as_slice(), &idx.to_be_bytes()].concat())
+}
+
+/// Append to the owner's history, returning the index of the new entry
+pub fn push_history<S: Storage>(storage: &mut S, owner: &CanonicalAddr, entry: &HistoryEntry) -> StdResult<u32> {
+    let len = history_len_read(storage, owner).may_load()?.unwrap_or(0);
+    history(storage, owner, len).save(entry)?;
+    history_len(storage, owner).save(&(len + 1))?;
+
+    Ok(len)
+}
+
+/// A page of the owner's history along with its total length
+pub fn history_page<S: Storage>(
+    storage: &S,
+    owner: &CanonicalAddr,
+    start: u32,
+    limit: u32
+) -> StdResult<(Vec<HistoryEntry>, u32)> {
+    let len = history_len_read(storage, owner).may_load()?.unwrap_or(0);
+    let end = len.min(start.saturating_add(limit));
+
+    let entries = (start..end)
+        .map(|idx| history_read(storage, owner, idx).load())
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok((entries, len))
+}
+
+pub fn history_ref<S: Storage>(storage: &mut S, action_id: u128) -> Singleton<S, HistoryRef> {
+    singleton(storage, &[HISTORY_REF_KEY, &action_id.to_be_bytes()].concat())
+}
+
+pub fn history_ref_read<S: Storage>(storage: &S, action_id: u128) -> ReadonlySingleton<S, HistoryRef> {
+    singleton_read(storage, &[HISTORY_REF_KEY, &action_id.to_be_bytes()].concat())
+}
+
+pub fn outbound<S: Storage>(storage: &mut S, action_id: u128) -> Singleton<S, OutboundAction> {
+    singleton(storage, &[OUTBOUND_KEY, &action_id.to_be_bytes()].concat())
+}
+
+pub fn outbound_read<S: Storage>(storage: &S, action_id: u128) -> ReadonlySingleton<S, OutboundAction> {
+    singleton_read(storage, &[OUTBOUND_KEY, &action_id.to_be_bytes()].concat())
+}
+
+/// Collection the wrapped token `token_id` was minted in
+pub fn wrapped_minter<S: Storage>(storage: &mut S, token_id: &str) -> Singleton<S, CanonicalAddr> {
+    singleton(storage, &[WRAPPED_MINTER_KEY, token_id.as_bytes()].concat())
+}
+
+pub fn wrapped_minter_read<S: Storage>(storage: &S, token_id: &str) -> ReadonlySingleton<S, CanonicalAddr> {
+    singleton_read(storage, &[WRAPPED_MINTER_KEY, token_id.as_bytes()].concat())
+}
+
+/// Foreign token a wrapped token minted by the bridge stands for
+pub fn foreign_token<S: Storage>(storage: &mut S, contract: &CanonicalAddr, token_id: &str) -> Singleton<S, ForeignToken> {
+    singleton(storage, &[FOREIGN_TOKEN_KEY, contract.as_slice(), b"/", token_id.as_bytes()].concat())
+}
+
+pub fn foreign_token_read<S: Storage>(storage: &S, contract: &CanonicalAddr, token_id: &str) -> ReadonlySingleton<S, ForeignToken> {
+    singleton_read(storage, &[FOREIGN_TOKEN_KEY, contract.as_slice(), b"/", token_id.as_bytes()].concat())
+}
@@ -1,11 +1,29 @@
+use crate::address::validate_address;
 use crate::contract as contract;
-use crate::msg::{InitMsg, QueryMsg, HandleMsg, BridgeAction, SetPause, Sig, ValidatorInfo, SetGroupKey, WhitelistNft};
+use crate::decode::{decode_log, BridgeEvent};
+use crate::encryption::decrypt_log;
+use crate::error::ContractError;
+use crate::signing::signing_digest;
+use crate::rate_limit;
+use crate::state::{config, config_read, OutboundAction, PREFIX_REVOKED_PERMITS};
+use crate::relay::{relayed_action, PeerAction, Route};
+use crate::mock_relayer::MockRelayer;
+use crate::mock_snip721::{handle_and_execute, snip721_dependencies, Snip721Deps};
+use crate::events::{ActionCompleted, BridgeEventInfo, ExecutedEventInfo, GuardianPause, UnfreezeSnip721, UnfreezeSnip721Batch};
+use crate::msg::{InitMsg, WithdrawFees, QueryMsg, HandleMsg, BridgeAction, SetPause, Sig, ValidatorInfo, SetGroupKey, WhitelistNft, SetGuardians, UnwhitelistNft, WhitelistNftBatch, AddressFormat, RegisterChain, Direction, RateLimit, SetRateLimit, RateLimitResponse, RescueNative, RescueNft, MintArgs, ValidateTransferNft, ValidateTransferNftBatch, ValidateUnfreezeNft, ValidateUnfreezeNftBatch, UnfreezeArgs, InCustodyResponse, SetEventKey, CompletionKind, CompletionReceipt, ReceiptToken, HistoryEntry, HistoryKind, HistoryStatus, HistoryResponse, PausedResponse, GroupKeyResponse, ConfigResponse, WhitelistedResponse, GuardiansResponse, WhitelistResponse, ReceiptResponse, SignableMsg, SigningPayloadResponse, VerifySignatureResponse, ActionSpace, ActionStatus, ActionConsumedResponse, ActionsConsumedResponse, ConsumedActionsResponse, EventCntResponse, RefundFreeze, RefundWithdraw, OutboundResponse, ForeignToken, WrappedTokenResponse, ForeignTokenResponse, QueryWithPermit, ClaimTransfer, ClaimTransferBatch};
 use borsh::BorshSerialize;
 use rand_core::OsRng;
 use cosmwasm_std::testing::{mock_dependencies, mock_env, MockApi, MockQuerier};
-use cosmwasm_std::{to_binary, InitResponse, Env, Extern, MemoryStorage, CanonicalAddr, Binary};
+use cosmwasm_std::{from_binary, from_slice, to_binary, InitResponse, Env, Extern, MemoryStorage, Querier, CanonicalAddr, Binary, HumanAddr, Api, Coin, Uint128, BankMsg, CosmosMsg, log, LogAttribute, StdResult};
+use std::convert::TryInto;
+use serde::Deserialize;
+use proptest::prelude::*;
+use proptest::collection::vec as prop_vec;
 use ed25519_dalek::{Keypair, PublicKey, ExpandedSecretKey};
 use sha2::{Sha512, Digest};
+use x25519_dalek::StaticSecret;
+use k256::ecdsa::{signature::Signer, Signature, SigningKey};
+use secret_toolkit::permit::{validate, Permit, PermitParams, PermitSignature, PubKey, SignedPermit, TokenPermissions};
 
 const CHAIN_NONCE: u64 = 1;
 const ACTION_ID: u128 = 1;
@@ -14,8 +32,16 @@ fn ed25519_kp() -> Keypair {
     Keypair::generate(&mut OsRng)
 }
 
-fn init_func(pubk: PublicKey, deps: &mut Extern<MemoryStorage, MockApi, MockQuerier>, env: Env) -> InitResponse {
-    let msg = InitMsg { group_key: pubk.to_bytes(), chain_nonce: CHAIN_NONCE, whitelist: Vec::new() };
+fn init_func<A: Api, Q: Querier>(pubk: PublicKey, deps: &mut Extern<MemoryStorage, A, Q>, env: Env) -> InitResponse {
+    let msg = InitMsg {
+        group_key: pubk.to_bytes(),
+        chain_nonce: CHAIN_NONCE,
+        whitelist: Vec::new(),
+        guardians: vec![HumanAddr("guardian".into())],
+        rescue_delay: 0,
+        event_key: None,
+        entropy: Binary(b"entropy".to_vec())
+    };
 
     let res = contract::init(deps, env, msg).unwrap();
     return res;
@@ -38,20 +64,75 @@ fn gen_sig(kp: &Keypair, env: &Env, action_id: u128, context: &[u8], inner: impl
     }
 }
 
+/// `MockApi` folding case like bech32 decoding does, every spelling of an address is the same account
+#[derive(Copy, Clone)]
+struct CaseInsensitiveApi(MockApi);
+
+impl Api for CaseInsensitiveApi {
+    fn canonical_address(&self, human: &HumanAddr) -> StdResult<CanonicalAddr> {
+        self.0.canonical_address(&HumanAddr(human.0.to_lowercase()))
+    }
+
+    fn human_address(&self, canonical: &CanonicalAddr) -> StdResult<HumanAddr> {
+        self.0.human_address(canonical)
+    }
+}
+
+fn case_insensitive_dependencies() -> Extern<MemoryStorage, CaseInsensitiveApi, MockQuerier> {
+    let deps = mock_dependencies(20, &[]);
+    Extern { storage: deps.storage, api: CaseInsensitiveApi(deps.api), querier: deps.querier }
+}
+
 #[test]
 fn proper_initialization() {
     let kp = ed25519_kp();
     let env = mock_env("creator", &[]);
-    let mut deps = mock_dependencies(20, &[]);
+    let mut deps = mock_dependencies(20, &[Coin { denom: "SCRT".into(), amount: Uint128(5) }]);
 
     let res = init_func(kp.public.clone(), &mut deps, env);
     assert_eq!(0, res.messages.len());
 
     let res = contract::query(&deps, QueryMsg::GetPaused).unwrap();
-    assert_eq!(res, to_binary(&false).unwrap());
+    assert_eq!(res, to_binary(&PausedResponse { paused: false }).unwrap());
 
     let res = contract::query(&deps, QueryMsg::GetGroupKey).unwrap();
-    assert_eq!(res, to_binary(&kp.public.to_bytes()).unwrap())
+    assert_eq!(res, to_binary(&GroupKeyResponse { group_key: kp.public.to_bytes() }).unwrap());
+
+    let res: ConfigResponse = from_binary(&contract::query(&deps, QueryMsg::GetConfig).unwrap()).unwrap();
+    assert_eq!(res, ConfigResponse {
+        version: env!("CARGO_PKG_VERSION").into(),
+        paused: false,
+        group_key: kp.public.to_bytes(),
+        chain_nonce: CHAIN_NONCE,
+        event_cnt: Uint128(0),
+        guardians: vec![HumanAddr("guardian".into())],
+        event_key: None,
+        fee_denom: "SCRT".into(),
+        fee_balance: Uint128(5),
+        rescue_delay: 0,
+        chains: vec![]
+    });
+}
+
+#[test]
+fn init_requires_entropy() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = mock_dependencies(20, &[]);
+
+    let msg = InitMsg {
+        group_key: kp.public.to_bytes(),
+        chain_nonce: CHAIN_NONCE,
+        whitelist: Vec::new(),
+        guardians: Vec::new(),
+        rescue_delay: 0,
+        event_key: Some([7u8; 32]),
+        entropy: Binary(Vec::new())
+    };
+    assert!(contract::init(&mut deps, env.clone(), msg).is_err());
+
+    let msg = format!(r#"{{"group_key":{:?},"chain_nonce":1,"whitelist":[],"event_key":{:?}}}"#, kp.public.to_bytes(), [7u8; 32]);
+    assert!(from_slice::<InitMsg>(msg.as_bytes()).is_err());
 }
 
 #[test]
@@ -67,7 +148,7 @@ fn set_pause() {
     contract::handle(&mut deps, env, HandleMsg::SetPause { info, inner }).unwrap();
 
     let res = contract::query(&deps, QueryMsg::GetPaused).unwrap();
-    assert_eq!(res, to_binary(&true).unwrap());
+    assert_eq!(res, to_binary(&PausedResponse { paused: true }).unwrap());
 }
 
 #[test]
@@ -84,7 +165,7 @@ fn set_gk() {
     contract::handle(&mut deps, env, HandleMsg::SetGroupKey { info, inner }).unwrap();
 
     let res = contract::query(&deps, QueryMsg::GetGroupKey).unwrap();
-    assert_eq!(res, to_binary(kp2.public.as_bytes()).unwrap());
+    assert_eq!(res, to_binary(&GroupKeyResponse { group_key: kp2.public.to_bytes() }).unwrap());
 }
 
 #[test]
@@ -101,5 +182,2065 @@ fn add_whitelist() {
     contract::handle(&mut deps, env, HandleMsg::WhitelistNft { info, inner }).unwrap();
 
     let res = contract::query(&deps, QueryMsg::GetWhitelisted { addr }).unwrap();
-    assert_eq!(res, to_binary(&true).unwrap());
+    assert_eq!(res, to_binary(&WhitelistedResponse { whitelisted: true }).unwrap());
+}
+
+#[test]
+fn emergency_pause() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_func(kp.public.clone(), &mut deps, env);
+
+    let res = contract::handle(&mut deps, mock_env("intruder", &[]), HandleMsg::EmergencyPause {});
+    assert!(matches!(res, Err(ContractError::NotGuardian {})));
+
+    let res = contract::handle(&mut deps, mock_env("guardian", &[]), HandleMsg::EmergencyPause {}).unwrap();
+    assert_eq!(res.log, vec![
+        log("action", "emergency_pause"),
+        log("guardian_pause", r#"{"event_version":1,"guardian":"guardian"}"#)
+    ]);
+
+    let res = contract::query(&deps, QueryMsg::GetPaused).unwrap();
+    assert_eq!(res, to_binary(&PausedResponse { paused: true }).unwrap());
+}
+
+#[test]
+fn set_guardians() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_func(kp.public.clone(), &mut deps, env.clone());
+
+    let inner = SetGuardians(vec!["guardian2".into()]);
+    let info = gen_sig(&kp, &env, ACTION_ID, b"SetGuardians", inner.clone());
+    contract::handle(&mut deps, env, HandleMsg::SetGuardians { info, inner }).unwrap();
+
+    let res = contract::query(&deps, QueryMsg::GetGuardians).unwrap();
+    assert_eq!(res, to_binary(&GuardiansResponse { guardians: vec![HumanAddr("guardian2".into())] }).unwrap());
+
+    let res = contract::handle(&mut deps, mock_env("guardian", &[]), HandleMsg::EmergencyPause {});
+    assert!(res.is_err());
+}
+
+#[test]
+fn remove_whitelist() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_func(kp.public.clone(), &mut deps, env.clone());
+
+    let addrs: Vec<CanonicalAddr> = (1..=3).map(|i| CanonicalAddr(Binary(vec![i; 20]))).collect();
+    let inner = WhitelistNftBatch {
+        add: addrs.iter().map(|a| a.0.0.clone()).collect(),
+        remove: Vec::new()
+    };
+    let info = gen_sig(&kp, &env, ACTION_ID, b"WhitelistNftBatch", inner.clone());
+    contract::handle(&mut deps, env.clone(), HandleMsg::WhitelistNftBatch { info, inner }).unwrap();
+
+    let inner = UnwhitelistNft(addrs[0].0.0.clone());
+    let info = gen_sig(&kp, &env, ACTION_ID + 1, b"UnwhitelistNft", inner.clone());
+    contract::handle(&mut deps, env, HandleMsg::UnwhitelistNft { info, inner }).unwrap();
+
+    let res = contract::query(&deps, QueryMsg::GetWhitelisted { addr: addrs[0].clone() }).unwrap();
+    assert_eq!(res, to_binary(&WhitelistedResponse { whitelisted: false }).unwrap());
+
+    let res = contract::query(&deps, QueryMsg::ListWhitelisted { start: None, limit: None }).unwrap();
+    let contracts = vec![addrs[2].clone(), addrs[1].clone()]
+        .iter()
+        .map(|a| deps.api.human_address(a).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(res, to_binary(&WhitelistResponse { contracts }).unwrap());
+}
+
+#[test]
+fn destination_addresses() {
+    let secret = AddressFormat::Bech32 { hrp: "secret".into() };
+
+    assert!(validate_address(&AddressFormat::EvmHex, EVM_ADDR));
+    assert!(!validate_address(&AddressFormat::EvmHex, "5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"));
+    assert!(validate_address(&secret, "secret1qqqsyqcyq5rqwzqfpg9scrgwpugpzysnpn9nv9"));
+    assert!(!validate_address(&secret, "secret1qqqsyqcyq5rqwzqfpg9scrgwpugpzysnpn9nv8"));
+    assert!(!validate_address(&AddressFormat::Bech32 { hrp: "cosmos".into() }, "secret1qqqsyqcyq5rqwzqfpg9scrgwpugpzysnpn9nv9"));
+    assert!(validate_address(&AddressFormat::Elrond, "erd1qqqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qarc0snuthh9"));
+    assert!(!validate_address(&AddressFormat::Elrond, "secret1qqqsyqcyq5rqwzqfpg9scrgwpugpzysnpn9nv9"));
+    assert!(validate_address(&AddressFormat::Tezos, "tz1VSUr8wwNhLAzempoch5d6hLRiTh8Cjcjb"));
+    assert!(!validate_address(&AddressFormat::Tezos, "tz1VSUr8wwNhLAzempoch5d6hLRiTh8Cjcjc"));
+    assert!(validate_address(&AddressFormat::Solana, "11111111111111111111111111111111"));
+    assert!(!validate_address(&AddressFormat::Solana, EVM_ADDR));
+}
+
+#[test]
+fn freeze_rejects_destination() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_func(kp.public.clone(), &mut deps, env.clone());
+
+    let inner = RegisterChain {
+        chain_nonce: 2,
+        name: "Ethereum".into(),
+        address_format: AddressFormat::EvmHex
+    };
+    let info = gen_sig(&kp, &env, ACTION_ID, b"RegisterChain", inner.clone());
+    contract::handle(&mut deps, env, HandleMsg::RegisterChain { info, inner }).unwrap();
+
+    let freeze = |chain_nonce: u64, to: &str| HandleMsg::FreezeNft {
+        contract: HumanAddr("collection".into()),
+        contract_hash: String::new(),
+        token_id: "1".into(),
+        viewer: None,
+        to: to.into(),
+        chain_nonce,
+        minter: String::new()
+    };
+    match contract::handle(&mut deps, fee_env(), freeze(CHAIN_NONCE, EVM_ADDR)) {
+        Err(ContractError::SameChain {}) => {},
+        res => panic!("unexpected result {:?}", res)
+    }
+    match contract::handle(&mut deps, fee_env(), freeze(3, EVM_ADDR)) {
+        Err(ContractError::UnsupportedChain { chain_nonce }) => assert_eq!(chain_nonce, 3),
+        res => panic!("unexpected result {:?}", res)
+    }
+    match contract::handle(&mut deps, fee_env(), freeze(2, "0x5aAeb6053F3E94")) {
+        Err(ContractError::InvalidAddress { address }) => assert_eq!(address, "0x5aAeb6053F3E94"),
+        res => panic!("unexpected result {:?}", res)
+    }
+}
+
+#[test]
+fn rate_limit() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_func(kp.public.clone(), &mut deps, env.clone());
+
+    let inner = RegisterChain {
+        chain_nonce: 2,
+        name: "Ethereum".into(),
+        address_format: AddressFormat::EvmHex
+    };
+    let info = gen_sig(&kp, &env, ACTION_ID, b"RegisterChain", inner.clone());
+    contract::handle(&mut deps, env.clone(), HandleMsg::RegisterChain { info, inner }).unwrap();
+
+    let limit = RateLimit { max_tokens: 1, window_blocks: 100 };
+    let inner = SetRateLimit { direction: Direction::Outbound, collection: None, limit: Some(limit.clone()) };
+    let info = gen_sig(&kp, &env, ACTION_ID + 1, b"SetRateLimit", inner.clone());
+    contract::handle(&mut deps, env.clone(), HandleMsg::SetRateLimit { info, inner }).unwrap();
+
+    let res = contract::query(&deps, QueryMsg::GetRateLimit {
+        direction: Direction::Outbound,
+        collection: None,
+        height: env.block.height
+    }).unwrap();
+    let res: RateLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(res, RateLimitResponse { limit: Some(limit), remaining: Some(1) });
+
+    let msg = freeze_nft_batch(vec!["1".into(), "2".into()]);
+    match contract::handle(&mut deps, fee_env(), msg) {
+        Err(ContractError::RateLimitExceeded {}) => {},
+        res => panic!("unexpected result {:?}", res)
+    }
+}
+
+#[test]
+fn rate_limit_inbound() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = nft_setup(&kp, &env);
+
+    let limit = RateLimit { max_tokens: 1, window_blocks: 100 };
+    let inner = SetRateLimit { direction: Direction::Inbound, collection: None, limit: Some(limit) };
+    let info = gen_sig(&kp, &env, ACTION_ID + 10, b"SetRateLimit", inner.clone());
+    contract::handle(&mut deps, env.clone(), HandleMsg::SetRateLimit { info, inner }).unwrap();
+
+    let inner = transfer_nft("wrapped", "1", "user");
+    let info = gen_sig(&kp, &env, ACTION_ID + 11, b"ValidateTransferNft", inner.clone());
+    handle_and_execute(&mut deps, env.clone(), HandleMsg::ValidateTransferNft { info, inner }).unwrap();
+
+    let inner = transfer_nft("wrapped", "2", "user");
+    let info = gen_sig(&kp, &env, ACTION_ID + 12, b"ValidateTransferNft", inner.clone());
+    match contract::handle(&mut deps, env.clone(), HandleMsg::ValidateTransferNft { info, inner }) {
+        Err(ContractError::RateLimitExceeded {}) => {},
+        res => panic!("unexpected result {:?}", res)
+    }
+}
+
+#[test]
+fn rate_limit_per_collection() {
+    let mut storage = MemoryStorage::new();
+    let limited = CanonicalAddr(Binary(b"limited".to_vec()));
+    let other = CanonicalAddr(Binary(b"other".to_vec()));
+    let limit = RateLimit { max_tokens: 2, window_blocks: 100 };
+    crate::state::rate_limit(&mut storage, &Direction::Outbound, Some(&limited)).save(&limit).unwrap();
+
+    rate_limit::consume(&mut storage, Direction::Outbound, &limited, 2, 10).unwrap();
+    match rate_limit::consume(&mut storage, Direction::Outbound, &limited, 1, 10) {
+        Err(ContractError::RateLimitExceeded {}) => {},
+        res => panic!("unexpected result {:?}", res)
+    }
+    assert_eq!(rate_limit::remaining(&storage, &Direction::Outbound, Some(&limited), 10).unwrap(), Some(0));
+
+    // Other collections and the other direction are unaffected
+    rate_limit::consume(&mut storage, Direction::Outbound, &other, 5, 10).unwrap();
+    rate_limit::consume(&mut storage, Direction::Inbound, &limited, 5, 10).unwrap();
+    assert_eq!(rate_limit::remaining(&storage, &Direction::Outbound, Some(&other), 10).unwrap(), None);
+}
+
+#[test]
+fn rate_limit_any_spelling() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = case_insensitive_dependencies();
+
+    init_func(kp.public.clone(), &mut deps, env.clone());
+
+    let inner = RegisterChain {
+        chain_nonce: 2,
+        name: "Ethereum".into(),
+        address_format: AddressFormat::EvmHex
+    };
+    let info = gen_sig(&kp, &env, ACTION_ID, b"RegisterChain", inner.clone());
+    contract::handle(&mut deps, env.clone(), HandleMsg::RegisterChain { info, inner }).unwrap();
+
+    let limit = RateLimit { max_tokens: 1, window_blocks: 100 };
+    let inner = SetRateLimit { direction: Direction::Outbound, collection: Some("Collection".into()), limit: Some(limit.clone()) };
+    let info = gen_sig(&kp, &env, ACTION_ID + 1, b"SetRateLimit", inner.clone());
+    contract::handle(&mut deps, env.clone(), HandleMsg::SetRateLimit { info, inner }).unwrap();
+
+    let res = contract::query(&deps, QueryMsg::GetRateLimit {
+        direction: Direction::Outbound,
+        collection: Some("COLLECTION".into()),
+        height: env.block.height
+    }).unwrap();
+    let res: RateLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(res, RateLimitResponse { limit: Some(limit), remaining: Some(1) });
+
+    // Another spelling of the collection doesn't get a fresh window
+    let msg = freeze_nft_batch(vec!["1".into(), "2".into()]);
+    match contract::handle(&mut deps, fee_env(), msg) {
+        Err(ContractError::RateLimitExceeded {}) => {},
+        res => panic!("unexpected result {:?}", res)
+    }
+}
+
+#[test]
+fn rate_limit_window() {
+    let mut storage = MemoryStorage::new();
+    // 120 blocks split into slots of 10
+    let collection = CanonicalAddr(Binary(b"collection".to_vec()));
+    let limit = RateLimit { max_tokens: 2, window_blocks: 120 };
+    crate::state::rate_limit(&mut storage, &Direction::Outbound, None).save(&limit).unwrap();
+
+    rate_limit::consume(&mut storage, Direction::Outbound, &collection, 1, 5).unwrap();
+    rate_limit::consume(&mut storage, Direction::Outbound, &collection, 1, 65).unwrap();
+    match rate_limit::consume(&mut storage, Direction::Outbound, &collection, 1, 119) {
+        Err(ContractError::RateLimitExceeded {}) => {},
+        res => panic!("unexpected result {:?}", res)
+    }
+
+    // The first slot rolls off once the window has moved past it
+    assert_eq!(rate_limit::remaining(&storage, &Direction::Outbound, None, 120).unwrap(), Some(1));
+    rate_limit::consume(&mut storage, Direction::Outbound, &collection, 1, 120).unwrap();
+    assert_eq!(rate_limit::remaining(&storage, &Direction::Outbound, None, 185).unwrap(), Some(1));
+    assert_eq!(rate_limit::remaining(&storage, &Direction::Outbound, None, 240).unwrap(), Some(2));
+}
+
+#[test]
+fn rate_limit_huge_window() {
+    let mut storage = MemoryStorage::new();
+    let collection = CanonicalAddr(Binary(b"collection".to_vec()));
+    let limit = RateLimit { max_tokens: 1, window_blocks: u64::MAX };
+    crate::state::rate_limit(&mut storage, &Direction::Outbound, None).save(&limit).unwrap();
+
+    rate_limit::consume(&mut storage, Direction::Outbound, &collection, 1, u64::MAX).unwrap();
+    assert_eq!(rate_limit::remaining(&storage, &Direction::Outbound, None, u64::MAX).unwrap(), Some(0));
+}
+
+#[test]
+fn rescue() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_func(kp.public.clone(), &mut deps, env.clone());
+
+    let inner = RescueNative { denom: "uscrt".into(), amount: 10, to: "owner".into() };
+    let info = gen_sig(&kp, &env, ACTION_ID, b"RescueNative", inner.clone());
+    let res = contract::handle(&mut deps, env.clone(), HandleMsg::RescueNative { info, inner }).unwrap();
+    assert_eq!(res.messages, vec![CosmosMsg::Bank(BankMsg::Send {
+        from_address: env.contract.address.clone(),
+        to_address: HumanAddr("owner".into()),
+        amount: vec![Coin { denom: "uscrt".into(), amount: Uint128(10) }]
+    })]);
+    assert_eq!(res.log, vec![
+        log("action", "rescue_native"),
+        log(
+            "rescue",
+            r#"{"event_version":1,"contract":null,"token_id":null,"denom":"uscrt","amount":10,"to":"owner","forced":false}"#
+        )
+    ]);
+
+    let inner = RescueNft {
+        contract: "collection".into(),
+        contract_hash: String::new(),
+        token_id: "1".into(),
+        to: "owner".into(),
+        force: false
+    };
+    let info = gen_sig(&kp, &env, ACTION_ID + 1, b"RescueNft", inner.clone());
+    let res = contract::handle(&mut deps, env, HandleMsg::RescueNft { info, inner }).unwrap();
+    assert_eq!(res.messages.len(), 1);
+}
+
+#[test]
+fn rescue_timelock() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = mock_dependencies(20, &[]);
+
+    let msg = InitMsg {
+        group_key: kp.public.to_bytes(),
+        chain_nonce: CHAIN_NONCE,
+        whitelist: Vec::new(),
+        guardians: vec![HumanAddr("guardian".into())],
+        rescue_delay: 10,
+        event_key: None,
+        entropy: Binary(b"entropy".to_vec())
+    };
+    contract::init(&mut deps, env.clone(), msg).unwrap();
+    let res: ConfigResponse = from_binary(&contract::query(&deps, QueryMsg::GetConfig).unwrap()).unwrap();
+    assert_eq!(res.rescue_delay, 10);
+
+    let inner = RescueNative { denom: "uscrt".into(), amount: 10, to: "owner".into() };
+    let info = gen_sig(&kp, &env, ACTION_ID, b"RescueNative", inner.clone());
+    let res = contract::handle(&mut deps, env.clone(), HandleMsg::RescueNative { info, inner }).unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(res.log, vec![
+        log("action", "rescue_native"),
+        log(
+            "rescue_queued",
+            format!(
+                r#"{{"event_version":1,"action_id":1,"ready_at":{},"asset":{{"native":{{"denom":"uscrt","amount":10,"to":"owner"}}}}}}"#,
+                env.block.height + 10
+            )
+        )
+    ]);
+
+    let mut later = env.clone();
+    later.block.height += 9;
+    let err = contract::handle(&mut deps, later.clone(), HandleMsg::ExecuteRescue { action_id: ACTION_ID }).unwrap_err();
+    assert!(matches!(err, ContractError::RescueTimelocked { ready_at } if ready_at == env.block.height + 10));
+    assert_eq!(err.code(), 18);
+
+    // guardians can hold a rescue they don't trust until the group key unpauses
+    later.block.height += 1;
+    contract::handle(&mut deps, mock_env("guardian", &[]), HandleMsg::EmergencyPause {}).unwrap();
+    let err = contract::handle(&mut deps, later.clone(), HandleMsg::ExecuteRescue { action_id: ACTION_ID }).unwrap_err();
+    assert!(matches!(err, ContractError::Paused {}));
+
+    let inner = SetPause(false);
+    let info = gen_sig(&kp, &env, ACTION_ID + 1, b"SetPause", inner.clone());
+    contract::handle(&mut deps, env.clone(), HandleMsg::SetPause { info, inner }).unwrap();
+
+    let res = contract::handle(&mut deps, mock_env("anyone", &[]), HandleMsg::ExecuteRescue { action_id: ACTION_ID });
+    assert!(matches!(res, Err(ContractError::RescueTimelocked { .. })));
+    let res = contract::handle(&mut deps, later.clone(), HandleMsg::ExecuteRescue { action_id: ACTION_ID }).unwrap();
+    assert_eq!(res.messages, vec![CosmosMsg::Bank(BankMsg::Send {
+        from_address: env.contract.address.clone(),
+        to_address: HumanAddr("owner".into()),
+        amount: vec![Coin { denom: "uscrt".into(), amount: Uint128(10) }]
+    })]);
+    assert_eq!(res.log[0], log("action", "execute_rescue"));
+
+    let err = contract::handle(&mut deps, later, HandleMsg::ExecuteRescue { action_id: ACTION_ID }).unwrap_err();
+    assert!(matches!(err, ContractError::UnknownRescue { action_id: ACTION_ID }));
+    assert_eq!(err.code(), 17);
+}
+
+#[test]
+fn decode_events() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_func(kp.public.clone(), &mut deps, env);
+
+    let res = contract::handle(&mut deps, mock_env("guardian", &[]), HandleMsg::EmergencyPause {}).unwrap();
+    let decoded = decode_log(&res.log).unwrap();
+    assert_eq!(decoded.action, Some("emergency_pause".to_string()));
+    assert_eq!(decoded.events, vec![BridgeEvent::GuardianPause(GuardianPause {
+        event_version: GuardianPause::VERSION,
+        guardian: HumanAddr("guardian".into())
+    })]);
+
+    let info = BridgeEventInfo::new(&mock_env("user", &[]), u128::MAX, CHAIN_NONCE, 2, 100, EVM_ADDR.into());
+    let unfreeze = UnfreezeSnip721Batch {
+        event_version: UnfreezeSnip721Batch::VERSION,
+        token_ids: vec!["1".into(), "2".into()],
+        token_uris: vec!["ipfs://1".into(), "ipfs://2".into()],
+        foreign: vec![None, None],
+        burner: HumanAddr("burner".into())
+    };
+    let attrs: Vec<LogAttribute> = vec![
+        info.clone().try_into().unwrap(),
+        unfreeze.clone().try_into().unwrap(),
+        log("contract_address", "other")
+    ];
+    let decoded = decode_log(&attrs).unwrap();
+    assert_eq!(decoded.action, None);
+    assert_eq!(decoded.events, vec![
+        BridgeEvent::BridgeEventInfo(info),
+        BridgeEvent::UnfreezeSnip721Batch(unfreeze)
+    ]);
+
+
+    let future = r#"{"event_version":99,"guardian":"guardian","reason":"new field"}"#;
+    let decoded = decode_log(&[log("guardian_pause", future)]).unwrap();
+    assert_eq!(decoded.events, vec![BridgeEvent::Unsupported {
+        key: "guardian_pause".into(),
+        event_version: 99,
+        value: future.into()
+    }]);
+}
+
+#[test]
+fn decode_old_events() {
+    let v1 = [
+        log("bridge_event_info", r#"{"event_version":1,"action_id":7,"chain_nonce":2,"tx_fees":100,"to":"0xab"}"#),
+        log("unfreeze_snip721", r#"{"event_version":1,"token_uri":"ipfs://1","burner":"burner"}"#),
+        log("unfreeze_snip721_batch", r#"{"event_version":1,"token_uris":["ipfs://1","ipfs://2"],"burner":"burner"}"#)
+    ];
+    let decoded = decode_log(&v1).unwrap();
+    assert_eq!(decoded.events, vec![
+        BridgeEvent::BridgeEventInfo(BridgeEventInfo {
+            event_version: 1,
+            action_id: 7,
+            source_chain_nonce: 0,
+            chain_nonce: 2,
+            tx_fees: 100,
+            sender: HumanAddr::default(),
+            to: "0xab".into(),
+            block_height: 0,
+            block_time: 0
+        }),
+        BridgeEvent::UnfreezeSnip721(UnfreezeSnip721 {
+            event_version: 1,
+            token_id: String::new(),
+            token_uri: "ipfs://1".into(),
+            foreign: None,
+            burner: HumanAddr("burner".into())
+        }),
+        BridgeEvent::UnfreezeSnip721Batch(UnfreezeSnip721Batch {
+            event_version: 1,
+            token_ids: vec![],
+            token_uris: vec!["ipfs://1".into(), "ipfs://2".into()],
+            foreign: vec![],
+            burner: HumanAddr("burner".into())
+        })
+    ]);
+
+    let v2 = [
+        log(
+            "bridge_event_info",
+            r#"{"event_version":2,"action_id":7,"source_chain_nonce":1,"chain_nonce":2,"tx_fees":100,"sender":"user","to":"0xab","block_height":12345,"block_time":1571797419}"#
+        ),
+        log("unfreeze_snip721", r#"{"event_version":2,"token_id":"1","token_uri":"ipfs://1","burner":"burner"}"#),
+        log("unfreeze_snip721_batch", r#"{"event_version":2,"token_ids":["1","2"],"token_uris":["ipfs://1","ipfs://2"],"burner":"burner"}"#)
+    ];
+    let decoded = decode_log(&v2).unwrap();
+    assert_eq!(decoded.events, vec![
+        BridgeEvent::BridgeEventInfo(BridgeEventInfo {
+            event_version: 2,
+            action_id: 7,
+            source_chain_nonce: 1,
+            chain_nonce: 2,
+            tx_fees: 100,
+            sender: HumanAddr("user".into()),
+            to: "0xab".into(),
+            block_height: 12345,
+            block_time: 1571797419
+        }),
+        BridgeEvent::UnfreezeSnip721(UnfreezeSnip721 {
+            event_version: 2,
+            token_id: "1".into(),
+            token_uri: "ipfs://1".into(),
+            foreign: None,
+            burner: HumanAddr("burner".into())
+        }),
+        BridgeEvent::UnfreezeSnip721Batch(UnfreezeSnip721Batch {
+            event_version: 2,
+            token_ids: vec!["1".into(), "2".into()],
+            token_uris: vec!["ipfs://1".into(), "ipfs://2".into()],
+            foreign: vec![],
+            burner: HumanAddr("burner".into())
+        })
+    ]);
+}
+
+#[test]
+fn validate_transfer_event() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_func(kp.public.clone(), &mut deps, env.clone());
+
+    let inner = transfer_nft("minter", "1", "receiver");
+    let info = gen_sig(&kp, &env, ACTION_ID, b"ValidateTransferNft", inner.clone());
+    let res = contract::handle(&mut deps, env.clone(), HandleMsg::ValidateTransferNft { info, inner }).unwrap();
+    assert_eq!(res.messages.len(), 1);
+
+    let receipt = CompletionReceipt {
+        kind: CompletionKind::Transfer,
+        source_chain_nonce: 2,
+        action_id: ACTION_ID,
+        to: HumanAddr("receiver".into()),
+        tokens: vec![ReceiptToken { contract: HumanAddr("minter".into()), token_id: wrapped_id("1") }],
+        block_height: env.block.height
+    };
+    assert_eq!(res.data, Some(to_binary(&receipt).unwrap()));
+
+    let decoded = decode_log(&res.log).unwrap();
+    assert_eq!(decoded.action, Some("validate_transfer_nft".to_string()));
+    assert_eq!(decoded.events, vec![
+        BridgeEvent::ExecutedEventInfo(ExecutedEventInfo::new(&env, ACTION_ID, CHAIN_NONCE)),
+        BridgeEvent::ActionCompleted(ActionCompleted { event_version: ActionCompleted::VERSION, receipt: receipt.clone() })
+    ]);
+
+    let res = contract::query(&deps, QueryMsg::GetReceipt { source_chain_nonce: 2, action_id: ACTION_ID }).unwrap();
+    assert_eq!(res, to_binary(&ReceiptResponse { receipt: Some(receipt) }).unwrap());
+}
+
+#[test]
+fn encrypted_events() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_func(kp.public.clone(), &mut deps, env.clone());
+
+    let validator_secret = [7u8; 32];
+    let validator_key = x25519_dalek::PublicKey::from(&StaticSecret::from(validator_secret));
+    let inner = SetEventKey(Some(*validator_key.as_bytes()));
+    let info = gen_sig(&kp, &env, ACTION_ID, b"SetEventKey", inner.clone());
+    contract::handle(&mut deps, env.clone(), HandleMsg::SetEventKey { info, inner }).unwrap();
+
+    let inner = transfer_nft("minter", "1", "receiver");
+    let info = gen_sig(&kp, &env, ACTION_ID + 1, b"ValidateTransferNft", inner.clone());
+    let res = contract::handle(&mut deps, env.clone(), HandleMsg::ValidateTransferNft { info, inner }).unwrap();
+    assert_eq!(res.log.len(), 2);
+    assert_eq!(res.log[0], log("action", "validate_transfer_nft"));
+    assert_eq!(res.log[1].key, "encrypted_event");
+    assert!(!res.log[1].value.contains("receiver"));
+
+    assert!(decrypt_log(&[8u8; 32], &res.log).is_err());
+
+    let plain = decrypt_log(&validator_secret, &res.log).unwrap();
+    assert_eq!(plain[0], log("action", "validate_transfer_nft"));
+    let decoded = decode_log(&plain).unwrap();
+    assert_eq!(decoded.events[0], BridgeEvent::ExecutedEventInfo(ExecutedEventInfo::new(&env, ACTION_ID + 1, CHAIN_NONCE)));
+
+    // admin events stay readable by anyone auditing the bridge
+    let res = contract::handle(&mut deps, mock_env("guardian", &[]), HandleMsg::EmergencyPause {}).unwrap();
+    assert_eq!(res.log, vec![
+        log("action", "emergency_pause"),
+        log("guardian_pause", r#"{"event_version":1,"guardian":"guardian"}"#)
+    ]);
+}
+
+#[test]
+fn user_history() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_func(kp.public.clone(), &mut deps, env.clone());
+
+    let inner = transfer_nft("minter", "1", "receiver");
+    let info = gen_sig(&kp, &env, ACTION_ID, b"ValidateTransferNft", inner.clone());
+    contract::handle(&mut deps, env.clone(), HandleMsg::ValidateTransferNft { info, inner }).unwrap();
+
+    let set_key = HandleMsg::SetViewingKey { key: "hunter2".into(), padding: None };
+    contract::handle(&mut deps, mock_env("receiver", &[]), set_key).unwrap();
+
+    let query = |key: &str| QueryMsg::GetHistory {
+        address: HumanAddr("receiver".into()),
+        key: key.into(),
+        start: None,
+        limit: None
+    };
+    assert!(contract::query(&deps, query("wrong")).is_err());
+
+    let res: HistoryResponse = from_binary(&contract::query(&deps, query("hunter2")).unwrap()).unwrap();
+    assert_eq!(res, HistoryResponse {
+        entries: vec![HistoryEntry {
+            action_id: ACTION_ID,
+            kind: HistoryKind::ReceiveTransfer,
+            status: HistoryStatus::Completed,
+            chain_nonce: 2,
+            counterparty: None,
+            tokens: vec![ReceiptToken { contract: HumanAddr("minter".into()), token_id: wrapped_id("1") }],
+            block_height: env.block.height
+        }],
+        total: 1
+    });
+}
+
+fn permit(key: &SigningKey, contract: &HumanAddr, name: &str, permissions: Vec<TokenPermissions>) -> Permit {
+    let params = PermitParams {
+        allowed_tokens: vec![contract.clone()],
+        permit_name: name.into(),
+        chain_id: "secret-4".into(),
+        permissions
+    };
+    let signed = to_binary(&SignedPermit::from_params(&params)).unwrap();
+    let signature: Signature = key.sign(signed.as_slice());
+
+    Permit {
+        params,
+        signature: PermitSignature {
+            pub_key: PubKey {
+                r#type: "tendermint/PubKeySecp256k1".into(),
+                value: Binary(key.verifying_key().to_bytes().to_vec())
+            },
+            signature: Binary(signature.as_ref().to_vec())
+        }
+    }
+}
+
+#[test]
+fn user_history_with_permit() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    // long enough for bech32 account addresses
+    let mut deps = mock_dependencies(45, &[]);
+
+    init_func(kp.public.clone(), &mut deps, env.clone());
+
+    let key = SigningKey::from_bytes(&[1u8; 32]).unwrap();
+    let history_permit = permit(&key, &env.contract.address, "history", vec![TokenPermissions::History]);
+    let account = validate(&deps, PREFIX_REVOKED_PERMITS, &history_permit, env.contract.address.clone(), None).unwrap();
+
+    let inner = transfer_nft("minter", "1", &account);
+    let info = gen_sig(&kp, &env, ACTION_ID, b"ValidateTransferNft", inner.clone());
+    contract::handle(&mut deps, env.clone(), HandleMsg::ValidateTransferNft { info, inner }).unwrap();
+
+    let query = |permit: &Permit| QueryMsg::WithPermit {
+        permit: permit.clone(),
+        query: QueryWithPermit::GetHistory { start: None, limit: None }
+    };
+
+    let res: HistoryResponse = from_binary(&contract::query(&deps, query(&history_permit)).unwrap()).unwrap();
+    assert_eq!(res.total, 1);
+    assert_eq!(res.entries[0].kind, HistoryKind::ReceiveTransfer);
+    assert_eq!(res.entries[0].action_id, ACTION_ID);
+
+    let owner_permit = permit(&key, &env.contract.address, "owner", vec![TokenPermissions::Owner]);
+    match contract::query(&deps, query(&owner_permit)) {
+        Err(ContractError::PermitNotAllowed {}) => {},
+        res => panic!("unexpected result {:?}", res)
+    }
+
+    let other_key = SigningKey::from_bytes(&[2u8; 32]).unwrap();
+    let other_permit = permit(&other_key, &env.contract.address, "history", vec![TokenPermissions::History]);
+    let res: HistoryResponse = from_binary(&contract::query(&deps, query(&other_permit)).unwrap()).unwrap();
+    assert_eq!(res.total, 0);
+
+    let revoke = HandleMsg::RevokePermit { permit_name: "history".into(), padding: None };
+    contract::handle(&mut deps, mock_env(account.as_str(), &[]), revoke).unwrap();
+    assert!(contract::query(&deps, query(&history_permit)).is_err());
+}
+
+#[test]
+fn signing_payload() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_func(kp.public.clone(), &mut deps, env.clone());
+
+    let inner = SetPause(true);
+    let act = BridgeAction::new(&env, ACTION_ID, CHAIN_NONCE, inner.clone()).unwrap();
+    let raw_act = act.try_to_vec().unwrap();
+    let mut hasher = Sha512::new();
+    hasher.update(b"SetPause");
+    hasher.update(&raw_act);
+
+    let payload: SigningPayloadResponse = from_binary(&contract::query(&deps, QueryMsg::GetSigningPayload {
+        msg: SignableMsg::SetPause(inner.clone()),
+        action_id: ACTION_ID
+    }).unwrap()).unwrap();
+    assert_eq!(payload, SigningPayloadResponse {
+        context: Binary(b"SetPause".to_vec()),
+        action: Binary(raw_act),
+        digest: Binary(hasher.finalize().to_vec())
+    });
+
+    let info = gen_sig(&kp, &env, ACTION_ID, b"SetPause", inner.clone());
+    let verify = |deps: &Extern<MemoryStorage, MockApi, MockQuerier>, info: ValidatorInfo| -> VerifySignatureResponse {
+        from_binary(&contract::query(deps, QueryMsg::VerifySignature {
+            msg: HandleMsg::SetPause { info, inner: inner.clone() }
+        }).unwrap()).unwrap()
+    };
+
+    let res = verify(&deps, info.clone());
+    assert!(res.valid);
+    assert_eq!(res.reason, None);
+    assert_eq!(res.digest, payload.digest);
+
+    let wrong = gen_sig(&ed25519_kp(), &env, ACTION_ID, b"SetPause", inner.clone());
+    let res = verify(&deps, wrong);
+    assert!(!res.valid);
+    assert!(res.reason.unwrap().contains("signature"));
+
+    // signed under another context
+    let wrong = gen_sig(&kp, &env, ACTION_ID, b"SetGroupKey", inner.clone());
+    assert!(!verify(&deps, wrong).valid);
+
+    contract::handle(&mut deps, env, HandleMsg::SetPause { info: info.clone(), inner: inner.clone() }).unwrap();
+    let res = verify(&deps, info);
+    assert!(!res.valid);
+    assert!(res.reason.unwrap().contains("consumed"));
+
+    assert!(contract::query(&deps, QueryMsg::VerifySignature { msg: HandleMsg::EmergencyPause {} }).is_err());
+}
+
+#[test]
+fn consumed_actions() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_func(kp.public.clone(), &mut deps, env.clone());
+
+    let res = contract::query(&deps, QueryMsg::GetActionConfigConsumed { action: 3 }).unwrap();
+    assert_eq!(res, to_binary(&ActionConsumedResponse { consumed: false }).unwrap());
+
+    for id in &[1, 3, 5] {
+        let inner = SetPause(false);
+        let info = gen_sig(&kp, &env, *id, b"SetPause", inner.clone());
+        contract::handle(&mut deps, env.clone(), HandleMsg::SetPause { info, inner }).unwrap();
+    }
+
+    let res = contract::query(&deps, QueryMsg::GetActionConfigConsumed { action: 3 }).unwrap();
+    assert_eq!(res, to_binary(&ActionConsumedResponse { consumed: true }).unwrap());
+    let res = contract::query(&deps, QueryMsg::GetActionConsumed { action: 3 }).unwrap();
+    assert_eq!(res, to_binary(&ActionConsumedResponse { consumed: false }).unwrap());
+
+    let res: ActionsConsumedResponse = from_binary(&contract::query(&deps, QueryMsg::GetActionsConsumed {
+        space: ActionSpace::Config,
+        actions: vec![1, 2, u128::MAX]
+    }).unwrap()).unwrap();
+    assert_eq!(res.actions, vec![
+        ActionStatus { action: 1, consumed: true },
+        ActionStatus { action: 2, consumed: false },
+        ActionStatus { action: u128::MAX, consumed: false }
+    ]);
+
+    let res: ConsumedActionsResponse = from_binary(&contract::query(&deps, QueryMsg::ListActionsConsumed {
+        space: ActionSpace::Config,
+        start: 0,
+        end: Some(10),
+        limit: Some(2)
+    }).unwrap()).unwrap();
+    assert_eq!(res, ConsumedActionsResponse { actions: vec![1, 3], next: Some(4) });
+
+    let res: ConsumedActionsResponse = from_binary(&contract::query(&deps, QueryMsg::ListActionsConsumed {
+        space: ActionSpace::Config,
+        start: 4,
+        end: Some(10),
+        limit: None
+    }).unwrap()).unwrap();
+    assert_eq!(res, ConsumedActionsResponse { actions: vec![5], next: None });
+
+    let res: ConsumedActionsResponse = from_binary(&contract::query(&deps, QueryMsg::ListActionsConsumed {
+        space: ActionSpace::Validate,
+        start: 0,
+        end: None,
+        limit: None
+    }).unwrap()).unwrap();
+    assert_eq!(res, ConsumedActionsResponse { actions: vec![], next: Some(1000) });
+
+    assert!(contract::query(&deps, QueryMsg::GetActionsConsumed {
+        space: ActionSpace::Validate,
+        actions: (0..101).collect()
+    }).is_err());
+}
+
+#[test]
+fn contract_errors() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_func(kp.public.clone(), &mut deps, env.clone());
+
+    let inner = SetPause(true);
+    let bad = gen_sig(&ed25519_kp(), &env, ACTION_ID, b"SetPause", inner.clone());
+    let err = contract::handle(&mut deps, env.clone(), HandleMsg::SetPause { info: bad, inner: inner.clone() }).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidSignature {}));
+    assert_eq!(err.code(), 3);
+
+    let info = gen_sig(&kp, &env, ACTION_ID + 1, b"SetPause", inner.clone());
+    contract::handle(&mut deps, env.clone(), HandleMsg::SetPause { info: info.clone(), inner: inner.clone() }).unwrap();
+    let err = contract::handle(&mut deps, env.clone(), HandleMsg::SetPause { info, inner }).unwrap_err();
+    assert!(matches!(err, ContractError::DuplicateAction { action_id } if action_id == ACTION_ID + 1));
+    assert_eq!(err.to_string(), format!("E002 action {} was already consumed", ACTION_ID + 1));
+
+    let freeze = freeze_nft("1", "");
+    let err = contract::handle(&mut deps, mock_env("user", &[]), freeze.clone()).unwrap_err();
+    assert!(matches!(err, ContractError::Paused {}));
+    assert_eq!(err.code(), 1);
+
+    let inner = SetPause(false);
+    let info = gen_sig(&kp, &env, ACTION_ID + 2, b"SetPause", inner.clone());
+    contract::handle(&mut deps, env.clone(), HandleMsg::SetPause { info, inner }).unwrap();
+
+    let err = contract::handle(&mut deps, mock_env("user", &[]), freeze).unwrap_err();
+    assert!(matches!(err, ContractError::FeeRequired { .. }));
+    assert_eq!(err.code(), 5);
+
+    let err = contract::query(&deps, QueryMsg::GetHistory {
+        address: HumanAddr("user".into()),
+        key: "wrong".into(),
+        start: None,
+        limit: None
+    }).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidViewingKey {}));
+}
+
+fn nft_setup(kp: &Keypair, env: &Env) -> Snip721Deps {
+    let mut deps = snip721_dependencies();
+    init_func(kp.public.clone(), &mut deps, env.clone());
+
+    let inner = RegisterChain {
+        chain_nonce: 2,
+        name: "Ethereum".into(),
+        address_format: AddressFormat::EvmHex
+    };
+    let info = gen_sig(kp, env, ACTION_ID, b"RegisterChain", inner.clone());
+    contract::handle(&mut deps, env.clone(), HandleMsg::RegisterChain { info, inner }).unwrap();
+
+    let inner = WhitelistNft(deps.api.canonical_address(&HumanAddr("collection".into())).unwrap().0.0);
+    let info = gen_sig(kp, env, ACTION_ID + 1, b"WhitelistNft", inner.clone());
+    contract::handle(&mut deps, env.clone(), HandleMsg::WhitelistNft { info, inner }).unwrap();
+
+    deps.querier.add_collection("collection", &[]);
+    deps.querier.add_collection("wrapped", &[&env.contract.address.0]);
+
+    deps
+}
+
+fn in_custody(deps: &Snip721Deps, token_id: &str) -> bool {
+    let res: InCustodyResponse = from_binary(&contract::query(deps, QueryMsg::GetInCustody {
+        contract: HumanAddr("collection".into()),
+        token_id: token_id.into()
+    }).unwrap()).unwrap();
+
+    res.in_custody
+}
+
+/// Id the bridge mints token `token_id` of "collection" on chain 2 under
+fn wrapped_id(token_id: &str) -> String {
+    ForeignToken { chain_nonce: 2, contract: "collection".into(), token_id: token_id.into() }.wrapped_id().unwrap()
+}
+
+/// Account on chain 2 tokens are sent to
+const EVM_ADDR: &str = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+
+/// "user" paying the outbound fee
+fn fee_env() -> Env {
+    mock_env("user", &[Coin { denom: "SCRT".into(), amount: Uint128(1) }])
+}
+
+/// Freeze of token `token_id` of "collection" to `EVM_ADDR` on chain 2
+fn freeze_nft(token_id: &str, minter: &str) -> HandleMsg {
+    HandleMsg::FreezeNft {
+        contract: HumanAddr("collection".into()),
+        contract_hash: String::new(),
+        token_id: token_id.into(),
+        viewer: None,
+        to: EVM_ADDR.into(),
+        chain_nonce: 2,
+        minter: minter.into()
+    }
+}
+
+fn freeze_nft_batch(token_ids: Vec<String>) -> HandleMsg {
+    HandleMsg::FreezeNftBatch {
+        contract: HumanAddr("collection".into()),
+        contract_hash: String::new(),
+        token_ids,
+        viewer: None,
+        to: EVM_ADDR.into(),
+        chain_nonce: 2,
+        minter: String::new()
+    }
+}
+
+/// Transfer of token `token_id` of "collection" on chain 2 to `to`, minted by `minter`
+fn transfer_nft(minter: &str, token_id: &str, to: &str) -> ValidateTransferNft {
+    ValidateTransferNft {
+        mint_args: MintArgs {
+            minter: minter.into(),
+            minter_hash: String::new(),
+            token_uri: format!("ipfs://{}", token_id),
+            contract: "collection".into(),
+            token_id: token_id.into()
+        },
+        to: to.into(),
+        source_chain_nonce: 2
+    }
+}
+
+fn transfer_nft_batch(minter: &str, token_ids: &[&str], to: &str) -> ValidateTransferNftBatch {
+    ValidateTransferNftBatch {
+        mint_args: token_ids.iter().map(|tok| transfer_nft(minter, tok, to).mint_args).collect(),
+        to: to.into(),
+        source_chain_nonce: 2
+    }
+}
+
+#[test]
+fn wrapped_ids() {
+    // validators of other chains derive the same ids
+    assert_eq!(wrapped_id("1"), "931344972388261a6e91939f3db7b769bead72b113514d3490d7894226462213");
+
+    let ids = [
+        wrapped_id("1"),
+        wrapped_id("2"),
+        ForeignToken { chain_nonce: 3, contract: "collection".into(), token_id: "1".into() }.wrapped_id().unwrap(),
+        ForeignToken { chain_nonce: 2, contract: "other".into(), token_id: "1".into() }.wrapped_id().unwrap(),
+        // length prefixes keep shifted boundaries apart
+        ForeignToken { chain_nonce: 2, contract: "collection1".into(), token_id: String::new() }.wrapped_id().unwrap(),
+    ];
+    for (i, id) in ids.iter().enumerate() {
+        assert!(ids[i + 1..].iter().all(|other| other != id));
+    }
+
+    let deps = mock_dependencies(20, &[]);
+    let res: WrappedTokenResponse = from_binary(&contract::query(&deps, QueryMsg::GetWrappedToken {
+        source_chain_nonce: 2,
+        contract: "collection".into(),
+        token_id: "1".into()
+    }).unwrap()).unwrap();
+    assert_eq!(res, WrappedTokenResponse { token_id: wrapped_id("1"), contract: None });
+}
+
+#[test]
+fn freeze_and_unfreeze_nft() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = nft_setup(&kp, &env);
+    let bridge = env.contract.address.clone();
+
+    deps.querier.give("collection", "1", "user", Some("ipfs://1"));
+    deps.querier.approve_all("collection", "user", &bridge.0);
+
+    handle_and_execute(&mut deps, fee_env(), freeze_nft("1", EVM_ADDR)).unwrap();
+    assert_eq!(deps.querier.owner_of("collection", "1"), Some(bridge));
+    assert!(in_custody(&deps, "1"));
+
+    let inner = ValidateUnfreezeNft {
+        unfreeze_args: UnfreezeArgs {
+            contract: "collection".into(),
+            contract_hash: String::new(),
+            token_id: "1".into()
+        },
+        to: "receiver".into(),
+        source_chain_nonce: 2
+    };
+    let info = gen_sig(&kp, &env, ACTION_ID, b"ValidateUnfreezeNft", inner.clone());
+    handle_and_execute(&mut deps, env, HandleMsg::ValidateUnfreezeNft { info, inner }).unwrap();
+    assert_eq!(deps.querier.owner_of("collection", "1"), Some(HumanAddr("receiver".into())));
+    assert!(!in_custody(&deps, "1"));
+}
+
+#[test]
+fn rescue_in_custody() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = nft_setup(&kp, &env);
+    let bridge = env.contract.address.clone();
+
+    deps.querier.give("collection", "1", "user", Some("ipfs://1"));
+    deps.querier.approve_all("collection", "user", &bridge.0);
+
+    handle_and_execute(&mut deps, fee_env(), freeze_nft("1", EVM_ADDR)).unwrap();
+
+    let rescue = |force| RescueNft {
+        contract: "collection".into(),
+        contract_hash: String::new(),
+        token_id: "1".into(),
+        to: "owner".into(),
+        force
+    };
+
+    let inner = rescue(false);
+    let info = gen_sig(&kp, &env, ACTION_ID + 2, b"RescueNft", inner.clone());
+    match contract::handle(&mut deps, env.clone(), HandleMsg::RescueNft { info, inner }) {
+        Err(ContractError::InCustody { contract, token_id }) => {
+            assert_eq!((contract.as_str(), token_id.as_str()), ("collection", "1"));
+        },
+        res => panic!("unexpected result {:?}", res)
+    }
+    assert_eq!(deps.querier.owner_of("collection", "1"), Some(bridge));
+    assert!(in_custody(&deps, "1"));
+
+    let inner = rescue(true);
+    let info = gen_sig(&kp, &env, ACTION_ID + 3, b"RescueNft", inner.clone());
+    handle_and_execute(&mut deps, env, HandleMsg::RescueNft { info, inner }).unwrap();
+    assert_eq!(deps.querier.owner_of("collection", "1"), Some(HumanAddr("owner".into())));
+    assert!(!in_custody(&deps, "1"));
+}
+
+#[test]
+fn custody_any_spelling() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = nft_setup(&kp, &env);
+    let bridge = env.contract.address.clone();
+
+    deps.querier.give("collection", "1", "user", Some("ipfs://1"));
+    deps.querier.approve_all("collection", "user", &bridge.0);
+
+    handle_and_execute(&mut deps, fee_env(), freeze_nft("1", EVM_ADDR)).unwrap();
+
+    let mut deps = Extern { storage: deps.storage, api: CaseInsensitiveApi(deps.api), querier: deps.querier };
+    let res: InCustodyResponse = from_binary(&contract::query(&deps, QueryMsg::GetInCustody {
+        contract: HumanAddr("COLLECTION".into()),
+        token_id: "1".into()
+    }).unwrap()).unwrap();
+    assert!(res.in_custody);
+
+    let inner = RescueNft {
+        contract: "COLLECTION".into(),
+        contract_hash: String::new(),
+        token_id: "1".into(),
+        to: "owner".into(),
+        force: false
+    };
+    let info = gen_sig(&kp, &env, ACTION_ID + 2, b"RescueNft", inner.clone());
+    let res = contract::handle(&mut deps, env.clone(), HandleMsg::RescueNft { info, inner });
+    assert!(matches!(res, Err(ContractError::InCustody { .. })));
+
+    let inner = ValidateUnfreezeNft {
+        unfreeze_args: UnfreezeArgs {
+            contract: "Collection".into(),
+            contract_hash: String::new(),
+            token_id: "1".into()
+        },
+        to: "receiver".into(),
+        source_chain_nonce: 2
+    };
+    let info = gen_sig(&kp, &env, ACTION_ID, b"ValidateUnfreezeNft", inner.clone());
+    contract::handle(&mut deps, env, HandleMsg::ValidateUnfreezeNft { info, inner }).unwrap();
+
+    let res: InCustodyResponse = from_binary(&contract::query(&deps, QueryMsg::GetInCustody {
+        contract: HumanAddr("collection".into()),
+        token_id: "1".into()
+    }).unwrap()).unwrap();
+    assert!(!res.in_custody);
+}
+
+#[test]
+fn freeze_and_unfreeze_nft_batch() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = nft_setup(&kp, &env);
+    let bridge = env.contract.address.clone();
+
+    deps.querier.give("collection", "1", "user", None);
+    deps.querier.give("collection", "2", "user", None);
+    deps.querier.approve_all("collection", "user", &bridge.0);
+
+    handle_and_execute(&mut deps, fee_env(), freeze_nft_batch(vec!["1".into(), "2".into()])).unwrap();
+    for tok in &["1", "2"] {
+        assert_eq!(deps.querier.owner_of("collection", tok), Some(bridge.clone()));
+        assert!(in_custody(&deps, tok));
+    }
+
+    let inner = ValidateUnfreezeNftBatch {
+        unfreeze_args: ["1", "2"].iter().map(|tok| UnfreezeArgs {
+            contract: "collection".into(),
+            contract_hash: String::new(),
+            token_id: (*tok).into()
+        }).collect(),
+        to: "receiver".into(),
+        source_chain_nonce: 2
+    };
+    let info = gen_sig(&kp, &env, ACTION_ID, b"ValidateUnfreezeNftBatch", inner.clone());
+    handle_and_execute(&mut deps, env, HandleMsg::ValidateUnfreezeNftBatch { info, inner }).unwrap();
+    for tok in &["1", "2"] {
+        assert_eq!(deps.querier.owner_of("collection", tok), Some(HumanAddr("receiver".into())));
+        assert!(!in_custody(&deps, tok));
+    }
+}
+
+#[test]
+fn freeze_requires_whitelist() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = nft_setup(&kp, &env);
+
+    deps.querier.add_collection("unlisted", &[]);
+    deps.querier.give("unlisted", "1", "user", None);
+    deps.querier.approve_all("unlisted", "user", &env.contract.address.0);
+
+    let err = handle_and_execute(&mut deps, fee_env(), HandleMsg::FreezeNft {
+        contract: HumanAddr("unlisted".into()),
+        contract_hash: String::new(),
+        token_id: "1".into(),
+        viewer: None,
+        to: EVM_ADDR.into(),
+        chain_nonce: 2,
+        minter: String::new()
+    }).unwrap_err();
+    assert!(matches!(err, ContractError::NotWhitelisted { .. }));
+    assert_eq!(deps.querier.owner_of("unlisted", "1"), Some(HumanAddr("user".into())));
+}
+
+#[test]
+fn transfer_and_withdraw_nft() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = nft_setup(&kp, &env);
+
+    let inner = transfer_nft("wrapped", "1", "user");
+    let info = gen_sig(&kp, &env, ACTION_ID, b"ValidateTransferNft", inner.clone());
+    handle_and_execute(&mut deps, env.clone(), HandleMsg::ValidateTransferNft { info, inner }).unwrap();
+
+    let wrapped = wrapped_id("1");
+    let token = deps.querier.token("wrapped", &wrapped).unwrap();
+    assert_eq!(token.owner, HumanAddr("user".into()));
+    assert_eq!(token.public_metadata.clone().unwrap().token_uri, Some("ipfs://1".into()));
+
+    deps.querier.approve_all("wrapped", "user", &env.contract.address.0);
+    let res = handle_and_execute(&mut deps, fee_env(), HandleMsg::WithdrawNft {
+        burner: HumanAddr("wrapped".into()),
+        burner_hash: String::new(),
+        token_id: wrapped.clone(),
+        viewer: None,
+        to: EVM_ADDR.into(),
+        chain_nonce: 2
+    }).unwrap();
+    assert_eq!(res.log[2], log("unfreeze_snip721", format!(
+        r#"{{"event_version":3,"token_id":"{}","token_uri":"ipfs://1","foreign":{{"chain_nonce":2,"contract":"collection","token_id":"1"}},"burner":"wrapped"}}"#,
+        wrapped
+    )));
+    assert_eq!(deps.querier.owner_of("wrapped", &wrapped), None);
+}
+
+#[test]
+fn transfer_into_second_collection() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = nft_setup(&kp, &env);
+    deps.querier.add_collection("other_wrapped", &[&env.contract.address.0]);
+
+    let inner = transfer_nft("wrapped", "1", "user");
+    let info = gen_sig(&kp, &env, ACTION_ID, b"ValidateTransferNft", inner.clone());
+    handle_and_execute(&mut deps, env.clone(), HandleMsg::ValidateTransferNft { info, inner }).unwrap();
+
+    // the same foreign token can't be wrapped by another collection as well
+    let inner = transfer_nft("other_wrapped", "1", "user");
+    let info = gen_sig(&kp, &env, ACTION_ID + 1, b"ValidateTransferNft", inner.clone());
+    match handle_and_execute(&mut deps, env.clone(), HandleMsg::ValidateTransferNft { info, inner }) {
+        Err(ContractError::WrappedElsewhere { token_id, contract }) => {
+            assert_eq!(token_id, wrapped_id("1"));
+            assert_eq!(contract, HumanAddr("wrapped".into()));
+        },
+        res => panic!("unexpected result {:?}", res)
+    }
+    assert_eq!(deps.querier.token("other_wrapped", &wrapped_id("1")), None);
+
+    let res: WrappedTokenResponse = from_binary(&contract::query(&deps, QueryMsg::GetWrappedToken {
+        source_chain_nonce: 2,
+        contract: "collection".into(),
+        token_id: "1".into()
+    }).unwrap()).unwrap();
+    assert_eq!(res.contract, Some(HumanAddr("wrapped".into())));
+}
+
+#[test]
+fn withdraw_to_other_chain() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = nft_setup(&kp, &env);
+
+    let inner = RegisterChain {
+        chain_nonce: 3,
+        name: "Polygon".into(),
+        address_format: AddressFormat::EvmHex
+    };
+    let info = gen_sig(&kp, &env, ACTION_ID + 2, b"RegisterChain", inner.clone());
+    contract::handle(&mut deps, env.clone(), HandleMsg::RegisterChain { info, inner }).unwrap();
+
+    let inner = transfer_nft_batch("wrapped", &["1", "2"], "user");
+    let info = gen_sig(&kp, &env, ACTION_ID, b"ValidateTransferNftBatch", inner.clone());
+    handle_and_execute(&mut deps, env.clone(), HandleMsg::ValidateTransferNftBatch { info, inner }).unwrap();
+    deps.querier.approve_all("wrapped", "user", &env.contract.address.0);
+
+    // wrapped tokens of chain 2 can't be sent on to chain 3
+    let err = handle_and_execute(&mut deps, fee_env(), HandleMsg::WithdrawNft {
+        burner: HumanAddr("wrapped".into()),
+        burner_hash: String::new(),
+        token_id: wrapped_id("1"),
+        viewer: None,
+        to: EVM_ADDR.into(),
+        chain_nonce: 3
+    }).unwrap_err();
+    assert!(matches!(err, ContractError::WrongChain { chain_nonce: 2, .. }));
+    assert_eq!(err.code(), 27);
+
+    let err = handle_and_execute(&mut deps, fee_env(), HandleMsg::WithdrawNftBatch {
+        burner: HumanAddr("wrapped".into()),
+        burner_hash: String::new(),
+        token_ids: vec![wrapped_id("1"), wrapped_id("2")],
+        viewer: None,
+        to: EVM_ADDR.into(),
+        chain_nonce: 3
+    }).unwrap_err();
+    assert!(matches!(err, ContractError::WrongChain { chain_nonce: 2, .. }));
+
+    for tok in &["1", "2"] {
+        assert_eq!(deps.querier.owner_of("wrapped", &wrapped_id(tok)), Some(HumanAddr("user".into())));
+    }
+
+    // nor under another spelling of the collection
+    let mut deps = Extern { storage: deps.storage, api: CaseInsensitiveApi(deps.api), querier: deps.querier };
+    let res: ForeignTokenResponse = from_binary(&contract::query(&deps, QueryMsg::GetForeignToken {
+        contract: HumanAddr("Wrapped".into()),
+        token_id: wrapped_id("1")
+    }).unwrap()).unwrap();
+    assert_eq!(res.foreign.map(|f| f.chain_nonce), Some(2));
+
+    let err = contract::handle(&mut deps, fee_env(), HandleMsg::WithdrawNft {
+        burner: HumanAddr("WRAPPED".into()),
+        burner_hash: String::new(),
+        token_id: wrapped_id("1"),
+        viewer: None,
+        to: EVM_ADDR.into(),
+        chain_nonce: 3
+    }).unwrap_err();
+    assert!(matches!(err, ContractError::WrongChain { chain_nonce: 2, .. }));
+}
+
+#[test]
+fn transfer_and_withdraw_nft_batch() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = nft_setup(&kp, &env);
+
+    let inner = transfer_nft_batch("wrapped", &["1", "2"], "user");
+    let info = gen_sig(&kp, &env, ACTION_ID, b"ValidateTransferNftBatch", inner.clone());
+    handle_and_execute(&mut deps, env.clone(), HandleMsg::ValidateTransferNftBatch { info: info.clone(), inner: inner.clone() }).unwrap();
+    let wrapped: Vec<String> = ["1", "2"].iter().map(|tok| wrapped_id(tok)).collect();
+    for tok in wrapped.iter() {
+        assert_eq!(deps.querier.owner_of("wrapped", tok), Some(HumanAddr("user".into())));
+    }
+
+    // replays are rejected before anything is minted twice
+    let err = handle_and_execute(&mut deps, env.clone(), HandleMsg::ValidateTransferNftBatch { info, inner }).unwrap_err();
+    assert!(matches!(err, ContractError::DuplicateAction { .. }));
+
+    deps.querier.approve_all("wrapped", "user", &env.contract.address.0);
+    let res = handle_and_execute(&mut deps, fee_env(), HandleMsg::WithdrawNftBatch {
+        burner: HumanAddr("wrapped".into()),
+        burner_hash: String::new(),
+        token_ids: wrapped.clone(),
+        viewer: None,
+        to: EVM_ADDR.into(),
+        chain_nonce: 2
+    }).unwrap();
+    for tok in wrapped.iter() {
+        assert_eq!(deps.querier.owner_of("wrapped", tok), None);
+    }
+
+    let decoded = decode_log(&res.log).unwrap();
+    assert_eq!(decoded.events[1], BridgeEvent::UnfreezeSnip721Batch(UnfreezeSnip721Batch {
+        event_version: UnfreezeSnip721Batch::VERSION,
+        token_ids: wrapped,
+        token_uris: vec!["ipfs://1".into(), "ipfs://2".into()],
+        foreign: ["1", "2"].iter().map(|tok| Some(ForeignToken {
+            chain_nonce: 2,
+            contract: "collection".into(),
+            token_id: (*tok).into()
+        })).collect(),
+        burner: HumanAddr("wrapped".into())
+    }));
+}
+
+#[test]
+fn claim_transfer() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = nft_setup(&kp, &env);
+
+    let inner = transfer_nft("wrapped", "1", "user");
+    let claim = ClaimTransfer(inner.clone());
+    let info = gen_sig(&kp, &env, ACTION_ID, b"ClaimTransfer", claim.clone());
+
+    let res: VerifySignatureResponse = from_binary(&contract::query(&deps, QueryMsg::VerifySignature {
+        msg: HandleMsg::ClaimTransfer { info: info.clone(), inner: claim.clone() }
+    }).unwrap()).unwrap();
+    assert!(res.valid);
+
+    // a claim signature can't be pushed by anyone else
+    let res: VerifySignatureResponse = from_binary(&contract::query(&deps, QueryMsg::VerifySignature {
+        msg: HandleMsg::ValidateTransferNft { info: info.clone(), inner: inner.clone() }
+    }).unwrap()).unwrap();
+    assert!(!res.valid);
+    let pushed = gen_sig(&kp, &env, ACTION_ID + 5, b"ClaimTransfer", claim.clone());
+    let err = handle_and_execute(&mut deps, env.clone(), HandleMsg::ValidateTransferNft { info: pushed, inner: inner.clone() }).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidSignature {}));
+
+    // nor can a push signature be claimed
+    let push = gen_sig(&kp, &env, ACTION_ID + 6, b"ValidateTransferNft", inner.clone());
+    let err = handle_and_execute(&mut deps, mock_env("user", &[]), HandleMsg::ClaimTransfer { info: push, inner: claim.clone() }).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidSignature {}));
+
+    let err = handle_and_execute(&mut deps, mock_env("other", &[]), HandleMsg::ClaimTransfer { info: info.clone(), inner: claim.clone() }).unwrap_err();
+    assert!(matches!(err, ContractError::NotRecipient { .. }));
+    assert_eq!(deps.querier.token("wrapped", &wrapped_id("1")), None);
+
+    let res = handle_and_execute(&mut deps, mock_env("user", &[]), HandleMsg::ClaimTransfer { info: info.clone(), inner: claim }).unwrap();
+    assert_eq!(deps.querier.owner_of("wrapped", &wrapped_id("1")), Some(HumanAddr("user".into())));
+    let receipt: CompletionReceipt = from_binary(&res.data.unwrap()).unwrap();
+    assert_eq!(receipt.to, HumanAddr("user".into()));
+
+    // claimed and pushed transfers share the action id, neither can complete it twice
+    let info = gen_sig(&kp, &env, ACTION_ID, b"ValidateTransferNft", inner.clone());
+    let err = handle_and_execute(&mut deps, env.clone(), HandleMsg::ValidateTransferNft { info, inner }).unwrap_err();
+    assert!(matches!(err, ContractError::DuplicateAction { .. }));
+
+    let inner = transfer_nft_batch("wrapped", &["2", "3"], "user");
+    let claim = ClaimTransferBatch(inner);
+    let info = gen_sig(&kp, &env, ACTION_ID + 1, b"ClaimTransferBatch", claim.clone());
+    handle_and_execute(&mut deps, mock_env("user", &[]), HandleMsg::ClaimTransferBatch { info, inner: claim }).unwrap();
+    for tok in &["2", "3"] {
+        assert_eq!(deps.querier.owner_of("wrapped", &wrapped_id(tok)), Some(HumanAddr("user".into())));
+    }
+}
+
+fn outbound_action(deps: &Snip721Deps, action_id: u128) -> Option<OutboundAction> {
+    let res: OutboundResponse = from_binary(&contract::query(deps, QueryMsg::GetOutbound { action_id }).unwrap()).unwrap();
+
+    res.outbound
+}
+
+#[test]
+fn refund_freeze() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = nft_setup(&kp, &env);
+    let bridge = env.contract.address.clone();
+
+    deps.querier.give("collection", "1", "user", Some("ipfs://1"));
+    deps.querier.give("collection", "2", "user", Some("ipfs://2"));
+    deps.querier.approve_all("collection", "user", &bridge.0);
+
+    let fee_env = mock_env("user", &[Coin { denom: "SCRT".into(), amount: Uint128(5) }]);
+    handle_and_execute(&mut deps, fee_env, freeze_nft_batch(vec!["1".into(), "2".into()])).unwrap();
+    assert_eq!(outbound_action(&deps, 0), Some(OutboundAction {
+        kind: HistoryKind::Freeze,
+        sender: HumanAddr("user".into()),
+        contract: HumanAddr("collection".into()),
+        contract_hash: String::new(),
+        token_ids: vec!["1".into(), "2".into()],
+        token_uris: vec![],
+        fee: 5,
+        refunded: false
+    }));
+
+    // refunds of unknown actions or of the wrong kind are rejected
+    let inner = RefundFreeze { action_id: 1, refund_fee: false };
+    let info = gen_sig(&kp, &env, ACTION_ID, b"RefundFreeze", inner.clone());
+    let err = contract::handle(&mut deps, env.clone(), HandleMsg::RefundFreeze { info, inner }).unwrap_err();
+    assert!(matches!(err, ContractError::UnknownAction { action_id: 1 }));
+
+    let inner = RefundWithdraw { action_id: 0, refund_fee: false };
+    let info = gen_sig(&kp, &env, ACTION_ID, b"RefundWithdraw", inner.clone());
+    let err = contract::handle(&mut deps, env.clone(), HandleMsg::RefundWithdraw { info, inner }).unwrap_err();
+    assert!(matches!(err, ContractError::WrongRefund { action_id: 0 }));
+
+    let inner = RefundFreeze { action_id: 0, refund_fee: true };
+    let info = gen_sig(&kp, &env, ACTION_ID, b"RefundFreeze", inner.clone());
+    let res = contract::handle(&mut deps, env.clone(), HandleMsg::RefundFreeze { info, inner }).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(res.messages[1], CosmosMsg::Bank(BankMsg::Send {
+        from_address: bridge.clone(),
+        to_address: HumanAddr("user".into()),
+        amount: vec![Coin { denom: "SCRT".into(), amount: Uint128(5) }]
+    }));
+    deps.querier.execute(&bridge, &res.messages[0]).unwrap();
+    assert_eq!(deps.querier.owner_of("collection", "1"), Some(HumanAddr("user".into())));
+    assert_eq!(deps.querier.owner_of("collection", "2"), Some(HumanAddr("user".into())));
+    assert!(!in_custody(&deps, "1"));
+    assert!(!in_custody(&deps, "2"));
+    assert!(outbound_action(&deps, 0).unwrap().refunded);
+
+    let inner = RefundFreeze { action_id: 0, refund_fee: true };
+    let info = gen_sig(&kp, &env, ACTION_ID + 1, b"RefundFreeze", inner.clone());
+    let err = contract::handle(&mut deps, env, HandleMsg::RefundFreeze { info, inner }).unwrap_err();
+    assert!(matches!(err, ContractError::AlreadyRefunded { action_id: 0 }));
+}
+
+#[test]
+fn refund_refrozen() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = nft_setup(&kp, &env);
+    let bridge = env.contract.address.clone();
+
+    deps.querier.give("collection", "1", "user", Some("ipfs://1"));
+    deps.querier.approve_all("collection", "user", &bridge.0);
+    handle_and_execute(&mut deps, fee_env(), freeze_nft("1", EVM_ADDR)).unwrap();
+
+    // the first freeze completes and the token comes back to the user
+    let inner = ValidateUnfreezeNft {
+        unfreeze_args: UnfreezeArgs {
+            contract: "collection".into(),
+            contract_hash: String::new(),
+            token_id: "1".into()
+        },
+        to: "user".into(),
+        source_chain_nonce: 2
+    };
+    let info = gen_sig(&kp, &env, ACTION_ID, b"ValidateUnfreezeNft", inner.clone());
+    handle_and_execute(&mut deps, env.clone(), HandleMsg::ValidateUnfreezeNft { info, inner }).unwrap();
+    assert_eq!(deps.querier.owner_of("collection", "1"), Some(HumanAddr("user".into())));
+
+    handle_and_execute(&mut deps, fee_env(), freeze_nft("1", EVM_ADDR)).unwrap();
+    assert!(in_custody(&deps, "1"));
+
+    // the token is held for the second freeze now, refunding the first mustn't release it
+    let inner = RefundFreeze { action_id: 0, refund_fee: false };
+    let info = gen_sig(&kp, &env, ACTION_ID + 1, b"RefundFreeze", inner.clone());
+    let err = contract::handle(&mut deps, env.clone(), HandleMsg::RefundFreeze { info, inner }).unwrap_err();
+    assert!(matches!(err, ContractError::NotHeld { action_id: 0, ref token_id } if token_id == "1"));
+    assert_eq!(err.code(), 24);
+    assert!(in_custody(&deps, "1"));
+    assert!(!outbound_action(&deps, 0).unwrap().refunded);
+
+    let inner = RefundFreeze { action_id: 1, refund_fee: false };
+    let info = gen_sig(&kp, &env, ACTION_ID + 1, b"RefundFreeze", inner.clone());
+    let res = contract::handle(&mut deps, env, HandleMsg::RefundFreeze { info, inner }).unwrap();
+    deps.querier.execute(&bridge, &res.messages[0]).unwrap();
+    assert_eq!(deps.querier.owner_of("collection", "1"), Some(HumanAddr("user".into())));
+    assert!(!in_custody(&deps, "1"));
+}
+
+#[test]
+fn refund_withdraw() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = nft_setup(&kp, &env);
+    let bridge = env.contract.address.clone();
+
+    deps.querier.give("wrapped", "1", "user", Some("ipfs://1"));
+    deps.querier.approve_all("wrapped", "user", &bridge.0);
+
+    let fee_env = mock_env("user", &[Coin { denom: "SCRT".into(), amount: Uint128(5) }]);
+    handle_and_execute(&mut deps, fee_env, HandleMsg::WithdrawNft {
+        burner: HumanAddr("wrapped".into()),
+        burner_hash: String::new(),
+        token_id: "1".into(),
+        viewer: None,
+        to: EVM_ADDR.into(),
+        chain_nonce: 2
+    }).unwrap();
+    assert_eq!(deps.querier.token("wrapped", "1"), None);
+
+    let inner = RefundWithdraw { action_id: 0, refund_fee: true };
+    let info = gen_sig(&kp, &env, ACTION_ID, b"RefundWithdraw", inner.clone());
+    let res = contract::handle(&mut deps, env.clone(), HandleMsg::RefundWithdraw { info, inner }).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(res.messages[1], CosmosMsg::Bank(BankMsg::Send {
+        from_address: bridge.clone(),
+        to_address: HumanAddr("user".into()),
+        amount: vec![Coin { denom: "SCRT".into(), amount: Uint128(5) }]
+    }));
+    deps.querier.execute(&bridge, &res.messages[0]).unwrap();
+
+    let token = deps.querier.token("wrapped", "1").unwrap();
+    assert_eq!(token.owner, HumanAddr("user".into()));
+    assert_eq!(token.public_metadata.clone().unwrap().token_uri, Some("ipfs://1".into()));
+
+    let decoded = decode_log(&res.log).unwrap();
+    assert!(decoded.events.iter().any(|ev| matches!(ev, BridgeEvent::Refund(r) if r.fee == Some(5) && r.to == HumanAddr("user".into()))));
+
+    // the sender's history shows the withdraw as refunded
+    contract::handle(&mut deps, mock_env("user", &[]), HandleMsg::SetViewingKey { key: "key".into(), padding: None }).unwrap();
+    let res: HistoryResponse = from_binary(&contract::query(&deps, QueryMsg::GetHistory {
+        address: HumanAddr("user".into()),
+        key: "key".into(),
+        start: None,
+        limit: None
+    }).unwrap()).unwrap();
+    assert_eq!(res.entries[0].status, HistoryStatus::Refunded);
+
+    let inner = RefundWithdraw { action_id: 0, refund_fee: false };
+    let info = gen_sig(&kp, &env, ACTION_ID + 1, b"RefundWithdraw", inner.clone());
+    let err = contract::handle(&mut deps, env, HandleMsg::RefundWithdraw { info, inner }).unwrap_err();
+    assert!(matches!(err, ContractError::AlreadyRefunded { action_id: 0 }));
+}
+
+#[test]
+fn relay_outbound_events() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = nft_setup(&kp, &env);
+
+    deps.querier.give("collection", "1", "user", Some("ipfs://1"));
+    deps.querier.give("wrapped", "2", "user", Some("ipfs://2"));
+    deps.querier.approve_all("collection", "user", &env.contract.address.0);
+    deps.querier.approve_all("wrapped", "user", &env.contract.address.0);
+
+    let res = handle_and_execute(&mut deps, fee_env(), freeze_nft("1", "0x8f0483125FCb9aaAEFA9209D8E9d7b9C8B9Fb90F")).unwrap();
+
+    let route = Route { code_hash: "hash".into(), contract: None, burner: None };
+    let (peer, msg) = relayed_action(&decode_log(&res.log).unwrap(), &route).unwrap();
+    assert_eq!(peer, PeerAction { chain_nonce: CHAIN_NONCE, action_id: 0 });
+    assert_eq!(msg, SignableMsg::ValidateTransferNft(ValidateTransferNft {
+        mint_args: MintArgs {
+            minter: "0x8f0483125FCb9aaAEFA9209D8E9d7b9C8B9Fb90F".into(),
+            minter_hash: "hash".into(),
+            token_uri: "ipfs://1".into(),
+            contract: "collection".into(),
+            token_id: "1".into()
+        },
+        to: EVM_ADDR.into(),
+        source_chain_nonce: CHAIN_NONCE
+    }));
+
+    let res = handle_and_execute(&mut deps, fee_env(), HandleMsg::WithdrawNft {
+        burner: HumanAddr("wrapped".into()),
+        burner_hash: String::new(),
+        token_id: "2".into(),
+        viewer: None,
+        to: EVM_ADDR.into(),
+        chain_nonce: 2
+    }).unwrap();
+    let log = decode_log(&res.log).unwrap();
+
+    // the bridge didn't mint this token, so the event can't name the original collection
+    assert!(relayed_action(&log, &route).is_err());
+
+    let route = Route {
+        code_hash: "hash".into(),
+        contract: Some("0x8f0483125FCb9aaAEFA9209D8E9d7b9C8B9Fb90F".into()),
+        burner: Some("collection".into())
+    };
+    // only withdraws from the route's wrapped collection unfreeze its original
+    assert!(relayed_action(&log, &route).is_err());
+
+    let route = Route { burner: Some("wrapped".into()), ..route };
+    let (peer, msg) = relayed_action(&log, &route).unwrap();
+    assert_eq!(peer, PeerAction { chain_nonce: CHAIN_NONCE, action_id: 1 });
+    assert_eq!(msg, SignableMsg::ValidateUnfreezeNft(ValidateUnfreezeNft {
+        unfreeze_args: UnfreezeArgs {
+            contract: "0x8f0483125FCb9aaAEFA9209D8E9d7b9C8B9Fb90F".into(),
+            contract_hash: "hash".into(),
+            token_id: "2".into()
+        },
+        to: EVM_ADDR.into(),
+        source_chain_nonce: CHAIN_NONCE
+    }));
+
+    assert!(relayed_action(&decode_log(&[]).unwrap(), &route).is_err());
+}
+
+#[test]
+fn relayed_round_trip() {
+    let alice = "secret15xs6rgdp5xs6rgdp5xs6rgdp5xs6rgdpphufwn";
+    let bob = "secret1kzctpv9skzctpv9skzctpv9skzctpv9snft5mp";
+
+    let relayer = MockRelayer { key: ed25519_kp() };
+    let mut chain_a = relayer.chain(1, "bridge_a", &[2]);
+    let mut chain_b = relayer.chain(2, "bridge_b", &[1]);
+    relayer.add_collection(&mut chain_a, "collection");
+    relayer.add_wrapped_collection(&mut chain_b, "wrapped");
+
+    chain_a.deps.querier.give("collection", "1", alice, Some("ipfs://1"));
+    chain_a.deps.querier.approve_all("collection", alice, "bridge_a");
+
+    // alice sends her token to bob on chain b
+    let freeze = chain_a.handle(alice, HandleMsg::FreezeNft {
+        contract: HumanAddr("collection".into()),
+        contract_hash: String::new(),
+        token_id: "1".into(),
+        viewer: None,
+        to: bob.into(),
+        chain_nonce: 2,
+        minter: "wrapped".into()
+    }).unwrap();
+    relayer.relay(&freeze.log, &Route { code_hash: String::new(), contract: None, burner: None }, &mut chain_b).unwrap();
+
+    assert_eq!(chain_a.deps.querier.owner_of("collection", "1"), Some(HumanAddr("bridge_a".into())));
+    let foreign = ForeignToken { chain_nonce: 1, contract: "collection".into(), token_id: "1".into() };
+    let wrapped_id = foreign.wrapped_id().unwrap();
+    let wrapped = chain_b.deps.querier.token("wrapped", &wrapped_id).unwrap();
+    assert_eq!(wrapped.owner, HumanAddr(bob.into()));
+    assert_eq!(wrapped.public_metadata.clone().unwrap().token_uri, Some("ipfs://1".into()));
+
+    // the mapping is kept both ways
+    let wrapped_res: WrappedTokenResponse = from_binary(&contract::query(&chain_b.deps, QueryMsg::GetWrappedToken {
+        source_chain_nonce: 1,
+        contract: "collection".into(),
+        token_id: "1".into()
+    }).unwrap()).unwrap();
+    assert_eq!(wrapped_res, WrappedTokenResponse { token_id: wrapped_id.clone(), contract: Some(HumanAddr("wrapped".into())) });
+    let foreign_res: ForeignTokenResponse = from_binary(&contract::query(&chain_b.deps, QueryMsg::GetForeignToken {
+        contract: HumanAddr("wrapped".into()),
+        token_id: wrapped_id.clone()
+    }).unwrap()).unwrap();
+    assert_eq!(foreign_res.foreign, Some(foreign));
+
+    // a relayed action is only delivered once
+    assert!(matches!(
+        relayer.relay(&freeze.log, &Route { code_hash: String::new(), contract: None, burner: None }, &mut chain_b),
+        Err(ContractError::DuplicateAction { action_id: 1 })
+    ));
+
+    // bob sends it back to alice, unfreezing the original the withdraw event names
+    chain_b.deps.querier.approve_all("wrapped", bob, "bridge_b");
+    let res = chain_b.handle(bob, HandleMsg::WithdrawNft {
+        burner: HumanAddr("wrapped".into()),
+        burner_hash: String::new(),
+        token_id: wrapped_id.clone(),
+        viewer: None,
+        to: alice.into(),
+        chain_nonce: 1
+    }).unwrap();
+    relayer.relay(&res.log, &Route { code_hash: String::new(), contract: None, burner: None }, &mut chain_a).unwrap();
+
+    assert_eq!(chain_b.deps.querier.token("wrapped", &wrapped_id), None);
+    assert_eq!(chain_a.deps.querier.owner_of("collection", "1"), Some(HumanAddr(alice.into())));
+    let res: InCustodyResponse = from_binary(&contract::query(&chain_a.deps, QueryMsg::GetInCustody {
+        contract: HumanAddr("collection".into()),
+        token_id: "1".into()
+    }).unwrap()).unwrap();
+    assert!(!res.in_custody);
+}
+
+/// Routes the client's smart queries to the bridge under test
+#[cfg(feature = "client")]
+struct BridgeQuerier<'a>(&'a Snip721Deps);
+
+#[cfg(feature = "client")]
+impl Querier for BridgeQuerier<'_> {
+    fn raw_query(&self, bin_request: &[u8]) -> cosmwasm_std::QuerierResult {
+        use cosmwasm_std::{Empty, QueryRequest, StdError, WasmQuery};
+
+        match from_slice::<QueryRequest<Empty>>(bin_request) {
+            Ok(QueryRequest::Wasm(WasmQuery::Smart { msg, .. })) => Ok(from_slice(msg.as_slice())
+                .map_err(ContractError::from)
+                .and_then(|msg| contract::query(self.0, msg))
+                .map_err(|e| StdError::generic_err(e.to_string()))),
+            _ => self.0.querier.raw_query(bin_request),
+        }
+    }
+}
+
+#[cfg(feature = "client")]
+#[test]
+fn relayed_from_two_peers() {
+    let alice = "secret15xs6rgdp5xs6rgdp5xs6rgdp5xs6rgdpphufwn";
+    let bob = "secret1kzctpv9skzctpv9skzctpv9skzctpv9snft5mp";
+    let route = Route { code_hash: String::new(), contract: None, burner: None };
+
+    let relayer = MockRelayer { key: ed25519_kp() };
+    let mut chain_a = relayer.chain(1, "bridge_a", &[3]);
+    let mut chain_b = relayer.chain(2, "bridge_b", &[3]);
+    let mut chain_c = relayer.chain(3, "bridge_c", &[1, 2]);
+    relayer.add_wrapped_collection(&mut chain_c, "wrapped");
+
+    // both peers log their first outbound action under the same id
+    let mut logs = Vec::new();
+    for (chain, bridge) in [(&mut chain_a, "bridge_a"), (&mut chain_b, "bridge_b")] {
+        relayer.add_collection(chain, "collection");
+        chain.deps.querier.give("collection", "1", alice, Some("ipfs://1"));
+        chain.deps.querier.approve_all("collection", alice, bridge);
+
+        let res = chain.handle(alice, HandleMsg::FreezeNft {
+            contract: HumanAddr("collection".into()),
+            contract_hash: String::new(),
+            token_id: "1".into(),
+            viewer: None,
+            to: bob.into(),
+            chain_nonce: 3,
+            minter: "wrapped".into()
+        }).unwrap();
+        let (peer, _) = relayed_action(&decode_log(&res.log).unwrap(), &route).unwrap();
+        assert_eq!(peer.action_id, 0);
+        logs.push(res.log);
+    }
+
+    for log in &logs {
+        relayer.relay(log, &route, &mut chain_c).unwrap();
+    }
+    for chain_nonce in [1, 2] {
+        let foreign = ForeignToken { chain_nonce, contract: "collection".into(), token_id: "1".into() };
+        let wrapped = chain_c.deps.querier.token("wrapped", &foreign.wrapped_id().unwrap()).unwrap();
+        assert_eq!(wrapped.owner, HumanAddr(bob.into()));
+    }
+
+    // redelivery is still rejected
+    assert!(matches!(
+        relayer.relay(&logs[1], &route, &mut chain_c),
+        Err(ContractError::DuplicateAction { action_id: 2 })
+    ));
+}
+
+#[test]
+fn client_messages() {
+    use crate::client::{BridgeClient, Outbound};
+    use cosmwasm_std::WasmMsg;
+
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = nft_setup(&kp, &env);
+    deps.querier.give("collection", "1", "user", Some("ipfs://1"));
+    deps.querier.give("collection", "2", "user", Some("ipfs://2"));
+    deps.querier.approve_all("collection", "user", &env.contract.address.0);
+
+    let client = BridgeClient::new(env.contract.address.clone(), "bridge_hash".into());
+    let outbound = Outbound::new(
+        HumanAddr("collection".into()),
+        String::new(),
+        EVM_ADDR.into(),
+        2
+    ).minter("0x8f0483125FCb9aaAEFA9209D8E9d7b9C8B9Fb90F");
+    assert!(client.freeze(&outbound, Uint128(1)).is_err());
+
+    let outbound = outbound.tokens(vec!["1".to_string(), "2".to_string()]);
+    let (msg, send) = match client.freeze(&outbound, Uint128(1)).unwrap() {
+        CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, callback_code_hash, msg, send }) => {
+            assert_eq!(contract_addr, env.contract.address);
+            assert_eq!(callback_code_hash, "bridge_hash");
+            (msg, send)
+        }
+        msg => panic!("unexpected message {:?}", msg)
+    };
+    assert_eq!(msg.len() % 256, 0);
+    let msg: HandleMsg = from_slice(msg.as_slice()).unwrap();
+    assert_eq!(msg, outbound.freeze_msg().unwrap());
+    assert!(matches!(msg, HandleMsg::FreezeNftBatch { .. }));
+    handle_and_execute(&mut deps, mock_env("user", &send), msg).unwrap();
+
+    let querier = BridgeQuerier(&deps);
+    assert!(client.in_custody(&querier, HumanAddr("collection".into()), "2".into()).unwrap());
+    assert_eq!(client.event_cnt(&querier).unwrap(), 1);
+    assert_eq!(client.chain_nonce(&querier).unwrap(), CHAIN_NONCE);
+    assert!(client.action_consumed(&querier, ActionSpace::Config, ACTION_ID).unwrap());
+    assert!(!client.action_consumed(&querier, ActionSpace::Validate, ACTION_ID).unwrap());
+    assert!(client.history(&querier, HumanAddr("user".into()), "wrong".into(), None, None).is_err());
+}
+
+#[derive(Deserialize)]
+struct SigningVectors {
+    public_key: Binary,
+    vectors: Vec<SigningVector>
+}
+
+#[derive(Deserialize)]
+struct SigningVector {
+    name: String,
+    chain_nonce: u64,
+    sc_addr: HumanAddr,
+    action_id: u128,
+    msg: SignableMsg,
+    context: String,
+    action: Binary,
+    digest: Binary,
+    signature: Binary
+}
+
+/// Golden vectors shared with the non Rust validators, regenerate with `cargo run --example test_vectors`
+#[test]
+fn signed_payload_vectors() {
+    let vectors: SigningVectors = from_slice(include_bytes!("../vectors/signed_payloads.json")).unwrap();
+    let mut group_key = [0u8; 32];
+    group_key.copy_from_slice(vectors.public_key.as_slice());
+    assert!(!vectors.vectors.is_empty());
+
+    for v in vectors.vectors {
+        let action = v.msg.action_bytes(&v.sc_addr, v.action_id, v.chain_nonce).unwrap();
+        assert_eq!(v.msg.context(), v.context.as_bytes(), "{}", v.name);
+        assert_eq!(action, v.action.0, "{}", v.name);
+        assert_eq!(signing_digest(v.msg.context(), &action), v.digest.0, "{}", v.name);
+
+        let mut env = mock_env("creator", &[]);
+        env.contract.address = v.sc_addr.clone();
+        let mut deps = mock_dependencies(20, &[]);
+        contract::init(&mut deps, env.clone(), InitMsg {
+            group_key,
+            chain_nonce: v.chain_nonce,
+            whitelist: Vec::new(),
+            guardians: Vec::new(),
+            rescue_delay: 0,
+            event_key: None,
+            entropy: Binary(b"entropy".to_vec())
+        }).unwrap();
+
+        let mut sig = [0u8; 64];
+        sig.copy_from_slice(v.signature.as_slice());
+        let msg = v.msg.into_handle_msg(ValidatorInfo { action_id: v.action_id, sig: Sig(sig) });
+        contract::handle(&mut deps, env, msg).unwrap_or_else(|e| panic!("{} rejected: {:?}", v.name, e));
+    }
+}
+
+#[test]
+fn batch_size_limits() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = nft_setup(&kp, &env);
+
+    let withdraw = |token_ids: Vec<String>| HandleMsg::WithdrawNftBatch {
+        burner: HumanAddr("wrapped".into()),
+        burner_hash: String::new(),
+        token_ids,
+        viewer: None,
+        to: EVM_ADDR.into(),
+        chain_nonce: 2
+    };
+
+    let oversized: Vec<String> = (0..101).map(|i| i.to_string()).collect();
+    for msg in vec![freeze_nft_batch(vec![]), freeze_nft_batch(oversized.clone()), withdraw(vec![]), withdraw(oversized)] {
+        let err = contract::handle(&mut deps, fee_env(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::BatchSize { max: 100, .. }), "{:?}", err);
+    }
+
+    // rejected batches don't use up action ids
+    let res = contract::query(&deps, QueryMsg::GetEventCnt).unwrap();
+    assert_eq!(res, to_binary(&EventCntResponse { event_cnt: Uint128(0) }).unwrap());
+}
+
+#[test]
+fn action_id_edges() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = nft_setup(&kp, &env);
+
+    let inner = SetPause(false);
+    let info = gen_sig(&kp, &env, u128::MAX, b"SetPause", inner.clone());
+    contract::handle(&mut deps, env.clone(), HandleMsg::SetPause { info: info.clone(), inner: inner.clone() }).unwrap();
+    let err = contract::handle(&mut deps, env.clone(), HandleMsg::SetPause { info, inner }).unwrap_err();
+    assert!(matches!(err, ContractError::DuplicateAction { action_id: u128::MAX }));
+
+    let mut state = config_read(&deps.storage).load().unwrap();
+    state.event_cnt = Uint128(u128::MAX);
+    config(&mut deps.storage).save(&state).unwrap();
+
+    deps.querier.give("collection", "1", "user", None);
+    deps.querier.approve_all("collection", "user", &env.contract.address.0);
+    let err = handle_and_execute(&mut deps, fee_env(), freeze_nft("1", "")).unwrap_err();
+    assert!(matches!(err, ContractError::ActionIdOverflow {}));
+    assert_eq!(deps.querier.owner_of("collection", "1"), Some(HumanAddr("user".into())));
+}
+
+#[test]
+fn unusual_inputs() {
+    let kp = ed25519_kp();
+    let env = mock_env("creator", &[]);
+    let mut deps = nft_setup(&kp, &env);
+
+    // no size limit on metadata beyond what the collection accepts
+    let token_uri = "ipfs://".to_string() + &"a".repeat(64 * 1024);
+    let inner = ValidateTransferNft {
+        mint_args: MintArgs {
+            minter: "wrapped".into(),
+            minter_hash: String::new(),
+            token_uri: token_uri.clone(),
+            contract: "collection".into(),
+            token_id: "1".into()
+        },
+        to: "user".into(),
+        source_chain_nonce: 2
+    };
+    let info = gen_sig(&kp, &env, ACTION_ID, b"ValidateTransferNft", inner.clone());
+    handle_and_execute(&mut deps, env.clone(), HandleMsg::ValidateTransferNft { info, inner }).unwrap();
+    let token = deps.querier.token("wrapped", &wrapped_id("1")).unwrap();
+    assert_eq!(token.public_metadata.clone().unwrap().token_uri, Some(token_uri));
+
+    let err = contract::handle(&mut deps, fee_env(), HandleMsg::FreezeNft {
+        contract: HumanAddr("collection".into()),
+        contract_hash: String::new(),
+        token_id: "1".into(),
+        viewer: None,
+        to: "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAé".into(),
+        chain_nonce: 2,
+        minter: String::new()
+    }).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidAddress { .. }));
+
+    for addr in &["0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAé", "secret1ąąą", "tz1ü", "erd1🦀", "ユーザー"] {
+        for format in &[AddressFormat::EvmHex, AddressFormat::Bech32 { hrp: "secret".into() }, AddressFormat::Elrond, AddressFormat::Tezos, AddressFormat::Solana] {
+            assert!(!validate_address(format, addr));
+        }
+    }
+}
+
+fn arb_text() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "[a-z0-9]{3,20}",
+        any::<String>(),
+        Just(String::new()),
+        "\\PC{0,4096}",
+    ]
+}
+
+fn arb_addr() -> impl Strategy<Value = HumanAddr> {
+    prop_oneof![
+        Just(HumanAddr("collection".into())),
+        Just(HumanAddr("wrapped".into())),
+        arb_text().prop_map(HumanAddr)
+    ]
+}
+
+fn arb_to() -> impl Strategy<Value = String> {
+    prop_oneof![Just(EVM_ADDR.to_string()), arb_text()]
+}
+
+fn arb_token_ids() -> impl Strategy<Value = Vec<String>> {
+    prop_vec(prop_oneof![Just("1".to_string()), arb_text()], 0..150)
+}
+
+fn arb_mint_args() -> impl Strategy<Value = MintArgs> {
+    (arb_text(), arb_text(), arb_text(), arb_text(), arb_text()).prop_map(|(minter, minter_hash, token_uri, contract, token_id)| MintArgs {
+        minter, minter_hash, token_uri, contract, token_id
+    })
+}
+
+fn arb_unfreeze_args() -> impl Strategy<Value = UnfreezeArgs> {
+    (arb_text(), arb_text(), arb_text()).prop_map(|(contract, contract_hash, token_id)| UnfreezeArgs {
+        contract, contract_hash, token_id
+    })
+}
+
+fn arb_signable() -> impl Strategy<Value = SignableMsg> {
+    prop_oneof![
+        any::<bool>().prop_map(|p| SignableMsg::SetPause(SetPause(p))),
+        arb_text().prop_map(|to| SignableMsg::WithdrawFees(WithdrawFees(to))),
+        prop_vec(any::<u8>(), 0..64).prop_map(|a| SignableMsg::WhitelistNft(WhitelistNft(a))),
+        prop_vec(any::<u8>(), 0..64).prop_map(|a| SignableMsg::UnwhitelistNft(UnwhitelistNft(a))),
+        prop_vec(arb_text(), 0..8).prop_map(|g| SignableMsg::SetGuardians(SetGuardians(g))),
+        (any::<u64>(), arb_text()).prop_map(|(chain_nonce, name)| SignableMsg::RegisterChain(RegisterChain {
+            chain_nonce,
+            name,
+            address_format: AddressFormat::EvmHex
+        })),
+        (any::<u64>(), any::<u64>()).prop_map(|(max_tokens, window_blocks)| SignableMsg::SetRateLimit(SetRateLimit {
+            direction: Direction::Inbound,
+            collection: None,
+            limit: Some(RateLimit { max_tokens, window_blocks })
+        })),
+        (arb_text(), any::<u128>(), arb_text()).prop_map(|(denom, amount, to)| SignableMsg::RescueNative(RescueNative {
+            denom, amount, to
+        })),
+        (arb_mint_args(), arb_text(), any::<u64>()).prop_map(|(mint_args, to, source_chain_nonce)| {
+            SignableMsg::ValidateTransferNft(ValidateTransferNft { mint_args, to, source_chain_nonce })
+        }),
+        (prop_vec(arb_mint_args(), 0..150), arb_text(), any::<u64>()).prop_map(|(mint_args, to, source_chain_nonce)| {
+            SignableMsg::ValidateTransferNftBatch(ValidateTransferNftBatch { mint_args, to, source_chain_nonce })
+        }),
+        (arb_unfreeze_args(), arb_text(), any::<u64>()).prop_map(|(unfreeze_args, to, source_chain_nonce)| {
+            SignableMsg::ValidateUnfreezeNft(ValidateUnfreezeNft { unfreeze_args, to, source_chain_nonce })
+        }),
+        (prop_vec(arb_unfreeze_args(), 0..150), arb_text(), any::<u64>()).prop_map(|(unfreeze_args, to, source_chain_nonce)| {
+            SignableMsg::ValidateUnfreezeNftBatch(ValidateUnfreezeNftBatch { unfreeze_args, to, source_chain_nonce })
+        }),
+        (any::<u128>(), any::<bool>()).prop_map(|(action_id, refund_fee)| SignableMsg::RefundFreeze(RefundFreeze { action_id, refund_fee })),
+        (any::<u128>(), any::<bool>()).prop_map(|(action_id, refund_fee)| SignableMsg::RefundWithdraw(RefundWithdraw { action_id, refund_fee })),
+        (arb_mint_args(), arb_text(), any::<u64>()).prop_map(|(mint_args, to, source_chain_nonce)| {
+            SignableMsg::ClaimTransfer(ClaimTransfer(ValidateTransferNft { mint_args, to, source_chain_nonce }))
+        }),
+    ]
+}
+
+fn arb_unsigned() -> impl Strategy<Value = HandleMsg> {
+    prop_oneof![
+        Just(HandleMsg::EmergencyPause {}),
+        any::<u128>().prop_map(|action_id| HandleMsg::ExecuteRescue { action_id }),
+        arb_text().prop_map(|key| HandleMsg::SetViewingKey { key, padding: None }),
+        arb_text().prop_map(|entropy| HandleMsg::CreateViewingKey { entropy, padding: None }),
+        arb_text().prop_map(|permit_name| HandleMsg::RevokePermit { permit_name, padding: None }),
+        (arb_addr(), arb_text(), arb_to(), any::<u64>(), arb_text()).prop_map(|(contract, token_id, to, chain_nonce, minter)| HandleMsg::FreezeNft {
+            contract, contract_hash: String::new(), token_id, viewer: None, to, chain_nonce, minter
+        }),
+        (arb_addr(), arb_token_ids(), arb_to(), any::<u64>()).prop_map(|(contract, token_ids, to, chain_nonce)| HandleMsg::FreezeNftBatch {
+            contract, contract_hash: String::new(), token_ids, viewer: None, to, chain_nonce, minter: String::new()
+        }),
+        (arb_addr(), arb_text(), arb_to(), any::<u64>()).prop_map(|(burner, token_id, to, chain_nonce)| HandleMsg::WithdrawNft {
+            burner, burner_hash: String::new(), token_id, viewer: None, to, chain_nonce
+        }),
+        (arb_addr(), arb_token_ids(), arb_to(), any::<u64>()).prop_map(|(burner, token_ids, to, chain_nonce)| HandleMsg::WithdrawNftBatch {
+            burner, burner_hash: String::new(), token_ids, viewer: None, to, chain_nonce
+        }),
+    ]
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    /// Whatever a client submits, the contract answers with a response or an error, never a panic
+    #[test]
+    fn arbitrary_unsigned_msgs(msg in arb_unsigned(), sender in arb_text(), event_cnt in prop_oneof![Just(0u128), Just(u128::MAX), any::<u128>()]) {
+        let kp = ed25519_kp();
+        let env = mock_env("creator", &[]);
+        let mut deps = nft_setup(&kp, &env);
+        deps.querier.give("collection", "1", "user", Some("ipfs://1"));
+        deps.querier.give("wrapped", "1", "user", Some("ipfs://1"));
+        deps.querier.approve_all("collection", "user", &env.contract.address.0);
+        deps.querier.approve_all("wrapped", "user", &env.contract.address.0);
+
+        let mut state = config_read(&deps.storage).load().unwrap();
+        state.event_cnt = Uint128(event_cnt);
+        config(&mut deps.storage).save(&state).unwrap();
+
+        let _ = handle_and_execute(&mut deps, mock_env(sender, &[Coin { denom: "SCRT".into(), amount: Uint128(1) }]), msg);
+    }
+
+    /// Signed messages with arbitrary payloads and action ids, validly signed or not
+    #[test]
+    fn arbitrary_signed_msgs(msg in arb_signable(), action_id in 3u128.., valid in any::<bool>()) {
+        let kp = ed25519_kp();
+        let env = mock_env("creator", &[]);
+        let mut deps = nft_setup(&kp, &env);
+
+        let action = msg.action_bytes(&env.contract.address, action_id, CHAIN_NONCE).unwrap();
+        let digest = signing_digest(msg.context(), &action);
+        let secret: ExpandedSecretKey = (&kp.secret).into();
+        let mut sig = secret.sign(&digest, &kp.public).to_bytes();
+        if !valid {
+            sig[0] ^= 1;
+        }
+        let signed = msg.into_handle_msg(ValidatorInfo { action_id, sig: Sig(sig) });
+
+        // the dry run agrees with the signature check, ids 1 and 2 are used up by the setup
+        let res: VerifySignatureResponse = from_binary(&contract::query(&deps, QueryMsg::VerifySignature {
+            msg: signed.clone()
+        }).unwrap()).unwrap();
+        prop_assert_eq!(res.valid, valid);
+
+        let res = contract::handle(&mut deps, env, signed);
+        if !valid {
+            prop_assert!(res.is_err());
+        }
+    }
+
+    /// Messages that survive a JSON round trip decode to themselves
+    #[test]
+    fn handle_msg_json_roundtrip(msg in prop_oneof![arb_unsigned(), arb_signable().prop_map(|m| m.into_handle_msg(ValidatorInfo { action_id: 1, sig: Sig([0; 64]) }))]) {
+        let bin = to_binary(&msg).unwrap();
+        let decoded: HandleMsg = from_binary(&bin).unwrap();
+        prop_assert_eq!(decoded, msg);
+    }
 }
@@ -0,0 +1,39 @@
+use cosmwasm_std::{Binary, CanonicalAddr, Env, Storage};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+use crate::error::{ContractError, ContractResult};
+use crate::state::viewing_key_read;
+
+pub const VIEWING_KEY_PREFIX: &str = "api_key_";
+
+pub fn hash_key(key: &str) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&Sha256::digest(key.as_bytes()));
+
+    hash
+}
+
+/// Derive a fresh viewing key from the contract seed and caller supplied entropy
+pub fn new_key(seed: &[u8; 32], env: &Env, entropy: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(seed);
+    hasher.update(env.block.height.to_be_bytes());
+    hasher.update(env.block.time.to_be_bytes());
+    hasher.update(env.message.sender.0.as_bytes());
+    hasher.update(entropy.as_bytes());
+
+    format!("{}{}", VIEWING_KEY_PREFIX, Binary(hasher.finalize().to_vec()).to_base64())
+}
+
+/// Fail unless `key` is the owner's viewing key.
+/// The key is hashed even without a stored one and compared in constant time,
+/// so timing leaks neither whether one exists nor how much of it matched
+pub fn require_viewing_key<S: Storage>(storage: &S, owner: &CanonicalAddr, key: &str) -> ContractResult<()> {
+    let hash = hash_key(key);
+
+    match viewing_key_read(storage, owner).may_load()? {
+        Some(stored) if bool::from(stored[..].ct_eq(&hash[..])) => Ok(()),
+        _ => Err(ContractError::InvalidViewingKey {})
+    }
+}
@@ -1,18 +1,590 @@
-//! This integration test tries to run and call the generated wasm.
-//! It depends on a Wasm build being available, which you can create with `cargo wasm`.
-//! Then running `cargo integration-test` will validate we can properly call into that generated Wasm.
-//!
-//! You can easily convert unit tests to integration tests.
-//! 1. First copy them over verbatum,
-//! 2. Then change
-//!      let mut deps = mock_dependencies(20, &[]);
-//!    to
-//!      let mut deps = mock_instance(WASM, &[]);
-//! 3. If you access raw storage, where ever you see something like:
-//!      deps.storage.get(CONFIG_KEY).expect("no data stored");
-//!    replace it with:
-//!      deps.with_storage(|store| {
-//!          let data = store.get(CONFIG_KEY).expect("no data stored");
-//!          //...
-//!      });
-//! 4. Anywhere you see query(&deps, ...) you must replace it with query(&mut deps, ...)
+//! This integration test runs and calls the generated wasm.
+//! It depends on a Wasm build being available, which you can create with `make build-mainnet`.
+//! Then running `make integration-test` will validate we can properly call into that generated Wasm.
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use cosmwasm_schema::schema_for;
+use cosmwasm_std::testing::mock_env;
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, Binary, CanonicalAddr, Coin, Empty, Env, HumanAddr, QuerierResult,
+    QueryRequest, StdError, Uint128, WasmQuery,
+};
+use cosmwasm_vm::testing::{
+    handle, init, mock_dependencies, mock_instance, mock_instance_with_gas_limit, query, MockApi,
+    MockQuerier, MockStorage,
+};
+use cosmwasm_vm::{Api, Extern, FfiResult, Instance, Querier};
+use ed25519_dalek::{ExpandedSecretKey, Keypair};
+use rand_core::OsRng;
+use serde_json::json;
+
+use bridge::decode::{decode_log, BridgeEvent};
+use bridge::events::{
+    ActionCompleted, BridgeEventInfo, EncryptedEvent, ExecutedEventInfo, GuardianPause, Refund, Rescue,
+    RescueQueued, TransferSnip721, TransferSnip721Batch, UnfreezeSnip721, UnfreezeSnip721Batch,
+};
+use bridge::msg::*;
+use bridge::signing::signing_digest;
+use bridge::state::{OutboundAction, State};
+
+static WASM: &[u8] = include_bytes!("../target/wasm32-unknown-unknown/release/bridge.wasm");
+
+const CHAIN_NONCE: u64 = 1;
+const FOREIGN_NONCE: u64 = 2;
+const EVM_ADDR: &str = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+
+/// Tokens per batch in the gas tests
+const BATCH_SIZE: usize = 50;
+const GAS_LIMIT: u64 = 10_000_000_000;
+
+type VmInstance<Q = MockQuerier> = Instance<MockStorage, MockApi, Q>;
+
+/// Answers the `nft_dossier` queries of collections deployed next to the VM, anything else goes to `MockQuerier`
+struct CollectionQuerier {
+    base: MockQuerier,
+    /// Public token uri of each `(collection, token_id)`
+    tokens: BTreeMap<(String, String), String>,
+}
+
+impl Querier for CollectionQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> FfiResult<QuerierResult> {
+        let (contract_addr, msg) = match from_slice::<QueryRequest<Empty>>(bin_request) {
+            Ok(QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg, .. })) => (contract_addr, msg),
+            _ => return self.base.raw_query(bin_request),
+        };
+
+        let query: serde_json::Value = serde_json::from_slice(msg.as_slice()).unwrap();
+        let token_id = query["nft_dossier"]["token_id"].as_str().unwrap().to_string();
+        let res = match self.tokens.get(&(contract_addr.0, token_id.clone())) {
+            Some(token_uri) => to_binary(&json!({
+                "nft_dossier": {
+                    "owner": "user",
+                    "public_metadata": { "token_uri": token_uri, "extension": null },
+                    "private_metadata": null,
+                    "display_private_metadata_error": null,
+                    "owner_is_public": true,
+                    "private_metadata_is_public": false
+                }
+            })),
+            None => Err(StdError::generic_err(format!("token {} not found", token_id))),
+        };
+
+        Ok(Ok(res))
+    }
+}
+
+/// Instance next to "collection" and "wrapped", each holding tokens 1 to 3
+fn collection_instance() -> VmInstance<CollectionQuerier> {
+    let deps = mock_dependencies(20, &[]);
+    let mut tokens = BTreeMap::new();
+    for contract in &["collection", "wrapped"] {
+        for tok in &["1", "2", "3"] {
+            tokens.insert((contract.to_string(), tok.to_string()), format!("ipfs://{}", tok));
+        }
+    }
+    let querier = CollectionQuerier { base: deps.querier, tokens };
+
+    Instance::from_code(WASM, Extern { storage: deps.storage, api: deps.api, querier }, GAS_LIMIT).unwrap()
+}
+
+fn sign(kp: &Keypair, env: &Env, action_id: u128, msg: SignableMsg) -> HandleMsg {
+    let action = msg.action_bytes(&env.contract.address, action_id, CHAIN_NONCE).unwrap();
+    let digest = signing_digest(msg.context(), &action);
+
+    let secret: ExpandedSecretKey = (&kp.secret).into();
+    let sig = Sig(secret.sign(&digest, &kp.public).to_bytes());
+
+    msg.into_handle_msg(ValidatorInfo { action_id, sig })
+}
+
+fn setup<Q: Querier + 'static>(deps: &mut VmInstance<Q>, kp: &Keypair, env: &Env) {
+    let msg = InitMsg {
+        group_key: kp.public.to_bytes(),
+        chain_nonce: CHAIN_NONCE,
+        whitelist: Vec::new(),
+        guardians: vec![HumanAddr("guardian".into())],
+        rescue_delay: 0,
+        event_key: None,
+        entropy: Binary(b"entropy".to_vec())
+    };
+    let res = init(deps, env.clone(), msg).unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    let register = SignableMsg::RegisterChain(RegisterChain {
+        chain_nonce: FOREIGN_NONCE,
+        name: "Ethereum".into(),
+        address_format: AddressFormat::EvmHex
+    });
+    handle(deps, env.clone(), sign(kp, env, 0, register)).unwrap();
+}
+
+fn canonical<Q: Querier>(deps: &VmInstance<Q>, addr: &str) -> Vec<u8> {
+    deps.api.canonical_address(&HumanAddr(addr.into())).unwrap().0.0
+}
+
+#[test]
+fn proper_initialization() {
+    let kp = Keypair::generate(&mut OsRng);
+    let env = mock_env("creator", &[]);
+    let mut deps = mock_instance(WASM, &[]);
+    setup(&mut deps, &kp, &env);
+
+    let res: ConfigResponse = from_binary(&query(&mut deps, QueryMsg::GetConfig).unwrap()).unwrap();
+    assert_eq!(res.group_key, kp.public.to_bytes());
+    assert_eq!(res.chain_nonce, CHAIN_NONCE);
+    assert!(!res.paused);
+    assert_eq!(res.chains.len(), 1);
+}
+
+#[test]
+fn config_messages() {
+    let kp = Keypair::generate(&mut OsRng);
+    let env = mock_env("creator", &[]);
+    let mut deps = mock_instance(WASM, &[]);
+    setup(&mut deps, &kp, &env);
+
+    let collection = canonical(&deps, "collection");
+    let kp2 = Keypair::generate(&mut OsRng);
+    let msgs = vec![
+        SignableMsg::WhitelistNft(WhitelistNft(collection.clone())),
+        SignableMsg::UnwhitelistNft(UnwhitelistNft(collection.clone())),
+        SignableMsg::WhitelistNftBatch(WhitelistNftBatch { add: vec![collection.clone()], remove: vec![] }),
+        SignableMsg::SetGuardians(SetGuardians(vec!["guardian".into(), "guardian2".into()])),
+        SignableMsg::RegisterChain(RegisterChain {
+            chain_nonce: 3,
+            name: "Cosmos".into(),
+            address_format: AddressFormat::Bech32 { hrp: "cosmos".into() }
+        }),
+        SignableMsg::UnregisterChain(UnregisterChain(3)),
+        SignableMsg::SetRateLimit(SetRateLimit {
+            direction: Direction::Inbound,
+            collection: None,
+            limit: Some(RateLimit { max_tokens: 1000, window_blocks: 100 })
+        }),
+        SignableMsg::SetEventKey(SetEventKey(None)),
+        SignableMsg::WithdrawFees(WithdrawFees("treasury".into())),
+        SignableMsg::RescueNative(RescueNative { denom: "uscrt".into(), amount: 1, to: "treasury".into() }),
+        SignableMsg::RescueSnip20(RescueSnip20 {
+            contract: "token".into(),
+            contract_hash: String::new(),
+            amount: 1,
+            to: "treasury".into()
+        }),
+        SignableMsg::RescueNft(RescueNft {
+            contract: "collection".into(),
+            contract_hash: String::new(),
+            token_id: "1".into(),
+            to: "treasury".into(),
+            force: false
+        }),
+        SignableMsg::SetPause(SetPause(true)),
+        SignableMsg::SetPause(SetPause(false)),
+    ];
+    for (i, msg) in msgs.into_iter().enumerate() {
+        handle(&mut deps, env.clone(), sign(&kp, &env, i as u128 + 1, msg)).unwrap();
+    }
+
+    let res: WhitelistedResponse = from_binary(&query(&mut deps, QueryMsg::GetWhitelisted {
+        addr: CanonicalAddr(Binary(collection))
+    }).unwrap()).unwrap();
+    assert!(res.whitelisted);
+
+    // signed by a key that isn't the group key
+    let msg = sign(&kp2, &env, 100, SignableMsg::SetGroupKey(SetGroupKey(kp2.public.to_bytes())));
+    let err = handle(&mut deps, env.clone(), msg).unwrap_err();
+    assert!(format!("{:?}", err).contains("E003"));
+
+    let msg = sign(&kp, &env, 100, SignableMsg::SetGroupKey(SetGroupKey(kp2.public.to_bytes())));
+    handle(&mut deps, env, msg).unwrap();
+    let res: GroupKeyResponse = from_binary(&query(&mut deps, QueryMsg::GetGroupKey).unwrap()).unwrap();
+    assert_eq!(res.group_key, kp2.public.to_bytes());
+}
+
+#[test]
+fn unsigned_messages() {
+    let kp = Keypair::generate(&mut OsRng);
+    let env = mock_env("creator", &[]);
+    let mut deps = mock_instance(WASM, &[]);
+    setup(&mut deps, &kp, &env);
+
+    let res = handle(&mut deps, mock_env("user", &[]), HandleMsg::CreateViewingKey {
+        entropy: "entropy".into(),
+        padding: None
+    }).unwrap();
+    let key: ViewingKeyResponse = from_binary(&res.data.unwrap()).unwrap();
+
+    let res: HistoryResponse = from_binary(&query(&mut deps, QueryMsg::GetHistory {
+        address: HumanAddr("user".into()),
+        key: key.key,
+        start: None,
+        limit: None
+    }).unwrap()).unwrap();
+    assert_eq!(res.total, 0);
+
+    handle(&mut deps, mock_env("user", &[]), HandleMsg::SetViewingKey { key: "hunter2".into(), padding: None }).unwrap();
+    handle(&mut deps, mock_env("user", &[]), HandleMsg::RevokePermit { permit_name: "permit".into(), padding: None }).unwrap();
+
+    let err = handle(&mut deps, mock_env("user", &[]), HandleMsg::EmergencyPause {}).unwrap_err();
+    assert!(format!("{:?}", err).contains("E006"));
+    handle(&mut deps, mock_env("guardian", &[]), HandleMsg::EmergencyPause {}).unwrap();
+
+    let res: PausedResponse = from_binary(&query(&mut deps, QueryMsg::GetPaused).unwrap()).unwrap();
+    assert!(res.paused);
+}
+
+#[test]
+fn validate_messages() {
+    let kp = Keypair::generate(&mut OsRng);
+    let env = mock_env("creator", &[]);
+    let mut deps = mock_instance(WASM, &[]);
+    setup(&mut deps, &kp, &env);
+
+    let mint = |tok: &str| MintArgs {
+        minter: "wrapped".into(),
+        minter_hash: String::new(),
+        token_uri: format!("ipfs://{}", tok),
+        contract: "collection".into(),
+        token_id: tok.into()
+    };
+    let unfreeze = |tok: &str| UnfreezeArgs {
+        contract: "collection".into(),
+        contract_hash: String::new(),
+        token_id: tok.into()
+    };
+    let msgs = vec![
+        (SignableMsg::ValidateTransferNft(ValidateTransferNft {
+            mint_args: mint("1"),
+            to: "user".into(),
+            source_chain_nonce: FOREIGN_NONCE
+        }), 1),
+        (SignableMsg::ValidateTransferNftBatch(ValidateTransferNftBatch {
+            mint_args: vec![mint("2"), mint("3")],
+            to: "user".into(),
+            source_chain_nonce: FOREIGN_NONCE
+        }), 2),
+        (SignableMsg::ValidateUnfreezeNft(ValidateUnfreezeNft {
+            unfreeze_args: unfreeze("1"),
+            to: "user".into(),
+            source_chain_nonce: FOREIGN_NONCE
+        }), 1),
+        (SignableMsg::ValidateUnfreezeNftBatch(ValidateUnfreezeNftBatch {
+            unfreeze_args: vec![unfreeze("2"), unfreeze("3")],
+            to: "user".into(),
+            source_chain_nonce: FOREIGN_NONCE
+        }), 2),
+    ];
+    for (i, (msg, expected)) in msgs.into_iter().enumerate() {
+        let action_id = i as u128 + 1;
+        let res = handle(&mut deps, env.clone(), sign(&kp, &env, action_id, msg)).unwrap();
+        assert_eq!(res.messages.len(), expected);
+
+        let res: ReceiptResponse = from_binary(&query(&mut deps, QueryMsg::GetReceipt {
+            source_chain_nonce: FOREIGN_NONCE,
+            action_id
+        }).unwrap()).unwrap();
+        assert_eq!(res.receipt.unwrap().tokens.len(), expected);
+    }
+
+    let res: ActionsConsumedResponse = from_binary(&query(&mut deps, QueryMsg::GetActionsConsumed {
+        space: ActionSpace::Validate,
+        actions: vec![1, 2, 3, 4, 5]
+    }).unwrap()).unwrap();
+    let consumed: Vec<bool> = res.actions.into_iter().map(|a| a.consumed).collect();
+    assert_eq!(consumed, vec![true, true, true, true, false]);
+
+    // nothing was sent from this chain, so nothing can be refunded
+    let refunds = vec![
+        SignableMsg::RefundFreeze(RefundFreeze { action_id: 0, refund_fee: true }),
+        SignableMsg::RefundWithdraw(RefundWithdraw { action_id: 0, refund_fee: false }),
+    ];
+    for (i, msg) in refunds.into_iter().enumerate() {
+        let err = handle(&mut deps, env.clone(), sign(&kp, &env, 5 + i as u128, msg)).unwrap_err();
+        assert!(format!("{:?}", err).contains("E021"), "unexpected error {:?}", err);
+    }
+
+    // transfers can be claimed by their recipient only
+    let inner = ClaimTransfer(ValidateTransferNft { mint_args: mint("4"), to: "user".into(), source_chain_nonce: FOREIGN_NONCE });
+    let msg = sign(&kp, &env, 7, SignableMsg::ClaimTransfer(inner.clone()));
+    let (info, _) = msg.into_signed().unwrap();
+    let err = handle(&mut deps, mock_env("other", &[]), HandleMsg::ClaimTransfer { info: info.clone(), inner: inner.clone() }).unwrap_err();
+    assert!(format!("{:?}", err).contains("E025"), "unexpected error {:?}", err);
+    let res = handle(&mut deps, mock_env("user", &[]), HandleMsg::ClaimTransfer { info, inner }).unwrap();
+    assert_eq!(res.messages.len(), 1);
+}
+
+#[test]
+fn outbound_messages() {
+    let kp = Keypair::generate(&mut OsRng);
+    let env = mock_env("creator", &[]);
+    let mut deps = collection_instance();
+    setup(&mut deps, &kp, &env);
+
+    let collection = canonical(&deps, "collection");
+    let msg = sign(&kp, &env, 1, SignableMsg::WhitelistNft(WhitelistNft(collection)));
+    handle(&mut deps, env, msg).unwrap();
+
+    let fee_env = mock_env("user", &[Coin { denom: "SCRT".into(), amount: Uint128(1) }]);
+    let msgs = vec![
+        ("freeze_nft", vec!["1"], HandleMsg::FreezeNft {
+            contract: HumanAddr("collection".into()),
+            contract_hash: String::new(),
+            token_id: "1".into(),
+            viewer: None,
+            to: EVM_ADDR.into(),
+            chain_nonce: FOREIGN_NONCE,
+            minter: String::new()
+        }),
+        ("freeze_nft_batch", vec!["2", "3"], HandleMsg::FreezeNftBatch {
+            contract: HumanAddr("collection".into()),
+            contract_hash: String::new(),
+            token_ids: vec!["2".into(), "3".into()],
+            viewer: None,
+            to: EVM_ADDR.into(),
+            chain_nonce: FOREIGN_NONCE,
+            minter: String::new()
+        }),
+        ("withdraw_nft", vec!["1"], HandleMsg::WithdrawNft {
+            burner: HumanAddr("wrapped".into()),
+            burner_hash: String::new(),
+            token_id: "1".into(),
+            viewer: None,
+            to: EVM_ADDR.into(),
+            chain_nonce: FOREIGN_NONCE
+        }),
+        ("withdraw_nft_batch", vec!["2", "3"], HandleMsg::WithdrawNftBatch {
+            burner: HumanAddr("wrapped".into()),
+            burner_hash: String::new(),
+            token_ids: vec!["2".into(), "3".into()],
+            viewer: None,
+            to: EVM_ADDR.into(),
+            chain_nonce: FOREIGN_NONCE
+        }),
+    ];
+    for (action_id, (action, tokens, msg)) in msgs.into_iter().enumerate() {
+        let action_id = action_id as u128;
+        let res = handle(&mut deps, fee_env.clone(), msg).unwrap();
+        // a single transfer into the bridge or burn of every token
+        assert_eq!(res.messages.len(), 1);
+
+        let log = decode_log(&res.log).unwrap();
+        assert_eq!(log.action.as_deref(), Some(action));
+        assert_eq!(log.events[0], BridgeEvent::BridgeEventInfo(
+            BridgeEventInfo::new(&fee_env, action_id, CHAIN_NONCE, FOREIGN_NONCE, 1, EVM_ADDR.into())
+        ));
+        let (contract, token_ids, token_uris): (&str, Vec<String>, Vec<String>) = match &log.events[1] {
+            BridgeEvent::TransferSnip721(ev) => ("collection", vec![ev.info.token_id.clone()], vec![]),
+            BridgeEvent::TransferSnip721Batch(ev) => ("collection", ev.infos.iter().map(|i| i.token_id.clone()).collect(), vec![]),
+            BridgeEvent::UnfreezeSnip721(ev) => ("wrapped", vec![ev.token_id.clone()], vec![ev.token_uri.clone()]),
+            BridgeEvent::UnfreezeSnip721Batch(ev) => ("wrapped", ev.token_ids.clone(), ev.token_uris.clone()),
+            ev => panic!("unexpected event {:?}", ev)
+        };
+        assert_eq!(token_ids, tokens);
+
+        let res: OutboundResponse = from_binary(&query(&mut deps, QueryMsg::GetOutbound { action_id }).unwrap()).unwrap();
+        let sent: OutboundAction = res.outbound.unwrap();
+        assert_eq!((sent.contract, sent.token_ids, sent.token_uris), (HumanAddr(contract.into()), token_ids.clone(), token_uris));
+        assert_eq!(sent.sender, HumanAddr("user".into()));
+
+        // frozen tokens stay in custody until a signed unfreeze, withdrawn ones are burnt instead
+        for token_id in token_ids {
+            let res: InCustodyResponse = from_binary(&query(&mut deps, QueryMsg::GetInCustody {
+                contract: HumanAddr(contract.into()),
+                token_id
+            }).unwrap()).unwrap();
+            assert_eq!(res.in_custody, contract == "collection");
+        }
+    }
+
+    let res: EventCntResponse = from_binary(&query(&mut deps, QueryMsg::GetEventCnt).unwrap()).unwrap();
+    assert_eq!(res.event_cnt, Uint128(4));
+
+    let mut to_unsupported = HandleMsg::FreezeNft {
+        contract: HumanAddr("collection".into()),
+        contract_hash: String::new(),
+        token_id: "1".into(),
+        viewer: None,
+        to: EVM_ADDR.into(),
+        chain_nonce: 9,
+        minter: String::new()
+    };
+    let err = handle(&mut deps, fee_env.clone(), to_unsupported.clone()).unwrap_err();
+    assert!(format!("{:?}", err).contains("E008"));
+
+    if let HandleMsg::FreezeNft { ref mut chain_nonce, .. } = to_unsupported {
+        *chain_nonce = FOREIGN_NONCE;
+    }
+    let err = handle(&mut deps, mock_env("user", &[]), to_unsupported).unwrap_err();
+    assert!(format!("{:?}", err).contains("E005"));
+}
+
+#[test]
+fn queries() {
+    let kp = Keypair::generate(&mut OsRng);
+    let env = mock_env("creator", &[]);
+    let mut deps = mock_instance(WASM, &[]);
+    setup(&mut deps, &kp, &env);
+
+    let payload = SignableMsg::SetPause(SetPause(true));
+    let queries = vec![
+        QueryMsg::GetConfig,
+        QueryMsg::GetGroupKey,
+        QueryMsg::GetPaused,
+        QueryMsg::GetChainNonce,
+        QueryMsg::GetEventCnt,
+        QueryMsg::GetGuardians,
+        QueryMsg::GetWhitelisted { addr: CanonicalAddr(Binary(canonical(&deps, "collection"))) },
+        QueryMsg::ListWhitelisted { start: None, limit: None },
+        QueryMsg::GetChain { chain_nonce: FOREIGN_NONCE },
+        QueryMsg::ListChains,
+        QueryMsg::GetRateLimit { direction: Direction::Outbound, collection: None, height: env.block.height },
+        QueryMsg::GetInCustody { contract: HumanAddr("collection".into()), token_id: "1".into() },
+        QueryMsg::GetOutbound { action_id: 0 },
+        QueryMsg::GetWrappedToken { source_chain_nonce: FOREIGN_NONCE, contract: "collection".into(), token_id: "1".into() },
+        QueryMsg::GetForeignToken { contract: HumanAddr("wrapped".into()), token_id: "1".into() },
+        QueryMsg::GetReceipt { source_chain_nonce: FOREIGN_NONCE, action_id: 1 },
+        QueryMsg::GetSigningPayload { msg: payload.clone(), action_id: 1 },
+        QueryMsg::VerifySignature { msg: sign(&kp, &env, 1, payload) },
+        QueryMsg::GetActionConsumed { action: 1 },
+        QueryMsg::GetActionConfigConsumed { action: 0 },
+        QueryMsg::GetActionsConsumed { space: ActionSpace::Config, actions: vec![0, 1] },
+        QueryMsg::ListActionsConsumed { space: ActionSpace::Config, start: 0, end: Some(10), limit: None },
+    ];
+    for msg in queries {
+        query(&mut deps, msg.clone()).unwrap_or_else(|e| panic!("{:?} failed: {:?}", msg, e));
+    }
+
+    let res: VerifySignatureResponse = from_binary(&query(&mut deps, QueryMsg::VerifySignature {
+        msg: sign(&kp, &env, 1, SignableMsg::SetPause(SetPause(true)))
+    }).unwrap()).unwrap();
+    assert!(res.valid);
+
+    let err = query(&mut deps, QueryMsg::GetHistory {
+        address: HumanAddr("user".into()),
+        key: "wrong".into(),
+        start: None,
+        limit: None
+    }).unwrap_err();
+    assert!(format!("{:?}", err).contains("E013"));
+}
+
+fn batch_gas(msg: impl Fn(&[String]) -> SignableMsg) -> (u64, u64) {
+    let kp = Keypair::generate(&mut OsRng);
+    let env = mock_env("creator", &[]);
+    let mut deps = mock_instance_with_gas_limit(WASM, GAS_LIMIT);
+    setup(&mut deps, &kp, &env);
+
+    let mut used = |action_id: u128, tokens: &[String]| {
+        let before = deps.get_gas_left();
+        handle(&mut deps, env.clone(), sign(&kp, &env, action_id, msg(tokens))).unwrap();
+        before - deps.get_gas_left()
+    };
+
+    let tokens: Vec<String> = (0..BATCH_SIZE + 1).map(|i| i.to_string()).collect();
+    (used(1, &tokens[..1]), used(2, &tokens[1..]))
+}
+
+/// The signature check and config loads of a message are paid once per batch,
+/// so every token past the first may add at most half of what a single token message costs
+fn batch_budget(single: u64) -> u64 {
+    single + (BATCH_SIZE as u64 - 1) * single / 2
+}
+
+fn assert_batch_gas(name: &str, (single, batch): (u64, u64)) {
+    let budget = batch_budget(single);
+    assert!(batch <= budget, "{} of {} tokens used {} gas, budget {} from {} for one token", name, BATCH_SIZE, batch, budget, single);
+}
+
+#[test]
+fn batch_gas_budgets() {
+    assert_batch_gas("ValidateTransferNftBatch", batch_gas(|tokens| {
+        SignableMsg::ValidateTransferNftBatch(ValidateTransferNftBatch {
+            mint_args: tokens.iter().map(|tok| MintArgs {
+                minter: "wrapped".into(),
+                minter_hash: String::new(),
+                token_uri: format!("ipfs://{}", tok),
+                contract: "collection".into(),
+                token_id: tok.clone()
+            }).collect(),
+            to: "user".into(),
+            source_chain_nonce: FOREIGN_NONCE
+        })
+    }));
+
+    assert_batch_gas("ValidateUnfreezeNftBatch", batch_gas(|tokens| {
+        SignableMsg::ValidateUnfreezeNftBatch(ValidateUnfreezeNftBatch {
+            unfreeze_args: tokens.iter().map(|tok| UnfreezeArgs {
+                contract: "collection".into(),
+                contract_hash: String::new(),
+                token_id: tok.clone()
+            }).collect(),
+            to: "user".into(),
+            source_chain_nonce: FOREIGN_NONCE
+        })
+    }));
+}
+
+macro_rules! check_schemas {
+    ($($ty:ty => $file:literal),* $(,)?) => {
+        let dir: PathBuf = [env!("CARGO_MANIFEST_DIR"), "schema"].iter().collect();
+        let expected = [$($file),*];
+        for entry in fs::read_dir(&dir).unwrap() {
+            let name = entry.unwrap().file_name().into_string().unwrap();
+            assert!(expected.contains(&name.as_str()), "{} isn't exported by examples/schema.rs", name);
+        }
+        $(
+            let path = dir.join($file);
+            let stored: serde_json::Value = serde_json::from_slice(
+                &fs::read(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+            ).unwrap();
+            let generated = serde_json::to_value(&schema_for!($ty)).unwrap();
+            assert!(stored == generated, "{} is stale, regenerate it with `make schema`", $file);
+        )*
+    };
+}
+
+#[test]
+fn schemas_up_to_date() {
+    check_schemas!(
+        InitMsg => "init_msg.json",
+        HandleMsg => "handle_msg.json",
+        QueryMsg => "query_msg.json",
+        State => "state.json",
+        ConfigResponse => "config_response.json",
+        PausedResponse => "paused_response.json",
+        GroupKeyResponse => "group_key_response.json",
+        ChainNonceResponse => "chain_nonce_response.json",
+        EventCntResponse => "event_cnt_response.json",
+        GuardiansResponse => "guardians_response.json",
+        WhitelistedResponse => "whitelisted_response.json",
+        WhitelistResponse => "whitelist_response.json",
+        ChainResponse => "chain_response.json",
+        ChainsResponse => "chains_response.json",
+        RateLimitResponse => "rate_limit_response.json",
+        ReceiptResponse => "receipt_response.json",
+        InCustodyResponse => "in_custody_response.json",
+        OutboundResponse => "outbound_response.json",
+        WrappedTokenResponse => "wrapped_token_response.json",
+        ForeignTokenResponse => "foreign_token_response.json",
+        ActionConsumedResponse => "action_consumed_response.json",
+        ActionsConsumedResponse => "actions_consumed_response.json",
+        ConsumedActionsResponse => "consumed_actions_response.json",
+        HistoryResponse => "history_response.json",
+        ViewingKeyResponse => "viewing_key_response.json",
+        SigningPayloadResponse => "signing_payload_response.json",
+        VerifySignatureResponse => "verify_signature_response.json",
+        BridgeEventInfo => "bridge_event_info.json",
+        ExecutedEventInfo => "executed_event_info.json",
+        ActionCompleted => "action_completed.json",
+        TransferSnip721 => "transfer_snip721.json",
+        TransferSnip721Batch => "transfer_snip721_batch.json",
+        UnfreezeSnip721 => "unfreeze_snip721.json",
+        UnfreezeSnip721Batch => "unfreeze_snip721_batch.json",
+        GuardianPause => "guardian_pause.json",
+        Rescue => "rescue.json",
+        RescueQueued => "rescue_queued.json",
+        Refund => "refund.json",
+        EncryptedEvent => "encrypted_event.json",
+    );
+}